@@ -0,0 +1,362 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use crate::EasyMutex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+/// An async counterpart of [`EasyMutex`], for use from async tasks.
+///
+/// Waiting for the lock suspends the task instead of blocking the executor thread. Waiters are
+/// served in the order they started waiting, and a waiting future that is dropped gives up its
+/// turn. Only `std` wakers are used, so it works with any executor.
+///
+/// The closures passed to [`EasyAsyncMutex::update`] and [`EasyAsyncMutex::with`] are
+/// synchronous, so the lock is never held across an `.await`.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::EasyAsyncMutex;
+/// use std::future::Future;
+/// use std::pin::pin;
+/// use std::task::{Context, Poll, Waker};
+///
+/// async fn deposit(account: &EasyAsyncMutex<u64>, amount: u64) -> u64 {
+///     account.update(|balance| {
+///         *balance += amount;
+///         *balance
+///     })
+///     .await
+/// }
+///
+/// let account = EasyAsyncMutex::new(10);
+///
+/// // Normally awaited from an executor; uncontended, it completes on the first poll.
+/// let mut task = pin!(deposit(&account, 5));
+/// let mut cx = Context::from_waker(Waker::noop());
+/// assert_eq!(task.as_mut().poll(&mut cx), Poll::Ready(15));
+/// ```
+#[derive(Default)]
+pub struct EasyAsyncMutex<T> {
+    queue: Arc<Queue>,
+    /// Only locked by the holder of the queue's permit, so it never blocks.
+    mutex: EasyMutex<T>,
+}
+
+/// A fair lock without data: the permit goes to the waiters in FIFO order.
+#[derive(Default)]
+struct Queue(Mutex<State>);
+
+#[derive(Default)]
+struct State {
+    locked: bool,
+    next_id: u64,
+    waiters: VecDeque<(u64, Waker)>,
+    /// The waiter the permit was handed to, until its future is polled again.
+    handoff: Option<u64>,
+}
+
+/// The future returned by [`Queue::acquire`].
+struct Acquire<'a> {
+    queue: &'a Queue,
+    /// Set once the future has joined the waiters.
+    id: Option<u64>,
+}
+
+/// Holding the lock; dropping it passes the permit to the next waiter.
+struct Permit<'a>(&'a Queue);
+
+impl Queue {
+    fn state(&self) -> MutexGuard<'_, State> {
+        // No user code runs under this lock, so it cannot be poisoned in a meaningful way.
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn acquire(&self) -> Acquire<'_> {
+        Acquire {
+            queue: self,
+            id: None,
+        }
+    }
+
+    fn release(&self) {
+        let mut state = self.state();
+        match state.waiters.pop_front() {
+            Some((id, waker)) => {
+                state.handoff = Some(id);
+                drop(state);
+                waker.wake();
+            }
+            None => state.locked = false,
+        }
+    }
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = Permit<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Permit<'a>> {
+        let queue = self.queue;
+        let mut state = queue.state();
+        match self.id {
+            // The lock is only free when nobody is waiting, so this cannot jump the queue.
+            None if !state.locked => {
+                state.locked = true;
+                Poll::Ready(Permit(queue))
+            }
+            None => {
+                let id = state.next_id;
+                state.next_id += 1;
+                state.waiters.push_back((id, cx.waker().clone()));
+                self.id = Some(id);
+                Poll::Pending
+            }
+            Some(id) if state.handoff == Some(id) => {
+                state.handoff = None;
+                self.id = None;
+                Poll::Ready(Permit(queue))
+            }
+            Some(id) => {
+                if let Some((_, waker)) = state.waiters.iter_mut().find(|(other, _)| *other == id) {
+                    waker.clone_from(cx.waker());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+        let mut state = self.queue.state();
+        if state.handoff == Some(id) {
+            // The permit was handed over but never taken: pass it on.
+            state.handoff = None;
+            drop(state);
+            self.queue.release();
+        } else {
+            state.waiters.retain(|(other, _)| *other != id);
+        }
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+impl<T> EasyAsyncMutex<T> {
+    /// Creates a new `EasyAsyncMutex` wrapping the given value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to wrap in a mutex.
+    ///
+    /// # Returns
+    ///
+    /// An `EasyAsyncMutex` instance holding the provided value.
+    pub fn new(value: T) -> Self {
+        Self {
+            queue: Arc::default(),
+            mutex: EasyMutex::new(value),
+        }
+    }
+
+    /// Waits for the lock, then returns a clone of the inner value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., a closure panicked while holding the lock).
+    pub async fn read(&self) -> T
+    where
+        T: Clone,
+    {
+        let _permit = self.queue.acquire().await;
+        self.mutex.read()
+    }
+
+    /// Waits for the lock, then replaces the inner value with `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., a closure panicked while holding the lock).
+    pub async fn write(&self, value: T) {
+        let _permit = self.queue.acquire().await;
+        self.mutex.write(value);
+    }
+
+    /// Waits for the lock, then runs `f` with a reference to the inner value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., a closure panicked while holding the lock).
+    pub async fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let _permit = self.queue.acquire().await;
+        self.mutex.with(f)
+    }
+
+    /// Waits for the lock, then runs `f` with a mutable reference to the inner value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., a closure panicked while holding the lock).
+    pub async fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let _permit = self.queue.acquire().await;
+        self.mutex.update(f)
+    }
+}
+
+impl<T> Clone for EasyAsyncMutex<T> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: Arc::clone(&self.queue),
+            mutex: self.mutex.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for EasyAsyncMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EasyAsyncMutex").field(&self.mutex).finish()
+    }
+}
+
+/// Enables `EasyAsyncMutex::from(value)` and `value.into()` syntax.
+impl<T> From<T> for EasyAsyncMutex<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EasyAsyncMutex;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    /// Minimal executor: polls `fut` on the current thread, parking it while pending.
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        struct Unpark(Thread);
+
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = pin!(fut);
+        loop {
+            match fut.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn read_write_update() {
+        let m = EasyAsyncMutex::new(1);
+        let clone = m.clone();
+        let value = block_on(async {
+            m.write(2).await;
+            clone.update(|v| *v += 1).await;
+            m.with(|v| *v * 10).await
+        });
+        assert_eq!(value, 30);
+        assert_eq!(block_on(clone.read()), 3);
+    }
+
+    #[test]
+    fn waiters_are_served_in_fifo_order() {
+        let m = EasyAsyncMutex::new(Vec::new());
+        let mut cx = Context::from_waker(Waker::noop());
+        let permit = block_on(m.queue.acquire());
+
+        let mut first = pin!(m.update(|v| v.push(1)));
+        let mut second = pin!(m.update(|v| v.push(2)));
+        let mut third = pin!(m.update(|v| v.push(3)));
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert!(third.as_mut().poll(&mut cx).is_pending());
+
+        drop(permit);
+        assert!(third.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert!(first.as_mut().poll(&mut cx).is_ready());
+        assert!(third.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_ready());
+        assert!(third.as_mut().poll(&mut cx).is_ready());
+
+        assert_eq!(block_on(m.read()), [1, 2, 3]);
+    }
+
+    #[test]
+    fn dropped_waiter_gives_up_its_turn() {
+        let m = EasyAsyncMutex::new(0);
+        let mut cx = Context::from_waker(Waker::noop());
+        let permit = block_on(m.queue.acquire());
+
+        let mut cancelled = Box::pin(m.write(1));
+        let mut skipped = Box::pin(m.write(2));
+        let mut last = pin!(m.update(|v| *v += 10));
+        assert!(cancelled.as_mut().poll(&mut cx).is_pending());
+        assert!(skipped.as_mut().poll(&mut cx).is_pending());
+        assert!(last.as_mut().poll(&mut cx).is_pending());
+
+        // One waiter leaves before its turn, the other after being handed the lock.
+        drop(skipped);
+        drop(permit);
+        drop(cancelled);
+        assert!(last.as_mut().poll(&mut cx).is_ready());
+        assert_eq!(block_on(m.read()), 10);
+    }
+
+    #[test]
+    fn tasks_on_several_threads() {
+        let m = EasyAsyncMutex::new(0);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let m = m.clone();
+                thread::spawn(move || {
+                    block_on(async {
+                        for _ in 0..1000 {
+                            m.update(|v| *v += 1).await;
+                        }
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(block_on(m.read()), 4000);
+    }
+}
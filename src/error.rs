@@ -0,0 +1,91 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use std::error::Error;
+use std::fmt;
+use std::sync::{PoisonError, TryLockError};
+
+/// The error returned by every fallible `EasyMutex` method.
+///
+/// Unlike `std::sync::PoisonError<MutexGuard<'_, T>>`, this error does not borrow the mutex:
+/// it is `Send + Sync + 'static`, so it can be propagated with `?` into
+/// `Box<dyn Error + Send + Sync>` or similar error types.
+///
+/// When the mutex is poisoned, the inner value can still be reached with
+/// [`EasyMutex::read_poisoned`](crate::EasyMutex::read_poisoned).
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyMutex, EasyMutexError};
+///
+/// fn double(shared: &EasyMutex<i32>) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
+///     let value = shared.read_result()?;
+///     shared.write_result(value * 2)?;
+///     Ok(value * 2)
+/// }
+///
+/// let shared = EasyMutex::new(21);
+/// assert_eq!(double(&shared).unwrap(), 42);
+///
+/// shared.with(|_| assert_eq!(shared.try_read(), Err(EasyMutexError::WouldBlock)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EasyMutexError {
+    /// Another thread panicked while holding the lock.
+    Poisoned,
+    /// The lock is currently held elsewhere and the operation does not wait.
+    WouldBlock,
+    /// The lock could not be acquired before the given timeout elapsed.
+    TimedOut,
+    /// Every handle to the watched mutex has been dropped, so it will not change anymore.
+    Closed,
+    /// The same mutex was passed more than once to
+    /// [`with_all_result`](crate::with_all_result), which cannot lock it twice.
+    Duplicate,
+}
+
+impl fmt::Display for EasyMutexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Poisoned => {
+                f.write_str("mutex poisoned: another thread panicked while holding the lock")
+            }
+            Self::WouldBlock => f.write_str("mutex is locked and the operation would block"),
+            Self::TimedOut => f.write_str("timed out waiting for the mutex lock"),
+            Self::Closed => f.write_str("the watched mutex has been dropped"),
+            Self::Duplicate => f.write_str("the same mutex was passed more than once"),
+        }
+    }
+}
+
+impl Error for EasyMutexError {}
+
+impl<G> From<PoisonError<G>> for EasyMutexError {
+    fn from(_: PoisonError<G>) -> Self {
+        Self::Poisoned
+    }
+}
+
+impl<G> From<TryLockError<G>> for EasyMutexError {
+    fn from(err: TryLockError<G>) -> Self {
+        match err {
+            TryLockError::Poisoned(_) => Self::Poisoned,
+            TryLockError::WouldBlock => Self::WouldBlock,
+        }
+    }
+}
@@ -0,0 +1,119 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use crate::EasyMutex;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+/// An [`EasyMutex`] handle compared and hashed by identity instead of by value.
+///
+/// Two `ByIdentity` are equal when they wrap clones of the same mutex, whatever the value
+/// inside. Comparing and hashing never lock the mutex, so `ByIdentity` can be used as a key in
+/// `HashSet`s and registries keyed by "which shared cell".
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{ByIdentity, EasyMutex};
+/// use std::collections::HashSet;
+///
+/// let a = EasyMutex::new(1);
+/// let b = EasyMutex::new(1);
+///
+/// let mut seen = HashSet::new();
+/// assert!(seen.insert(ByIdentity::from(a.clone())));
+/// assert!(seen.insert(ByIdentity::from(b)));
+/// assert!(!seen.insert(ByIdentity::from(a)));
+/// assert_eq!(seen.len(), 2);
+/// ```
+pub struct ByIdentity<T>(pub EasyMutex<T>);
+
+impl<T> ByIdentity<T> {
+    /// Unwraps the handle.
+    pub fn into_inner(self) -> EasyMutex<T> {
+        self.0
+    }
+}
+
+impl<T> PartialEq for ByIdentity<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ptr_eq(&other.0)
+    }
+}
+
+impl<T> Eq for ByIdentity<T> {}
+
+impl<T> Hash for ByIdentity<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.addr().hash(state);
+    }
+}
+
+impl<T> Clone for ByIdentity<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ByIdentity<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ByIdentity").field(&self.0).finish()
+    }
+}
+
+impl<T> Deref for ByIdentity<T> {
+    type Target = EasyMutex<T>;
+
+    fn deref(&self) -> &EasyMutex<T> {
+        &self.0
+    }
+}
+
+/// Enables `ByIdentity::from(mutex)` syntax.
+impl<T> From<EasyMutex<T>> for ByIdentity<T> {
+    fn from(mutex: EasyMutex<T>) -> Self {
+        Self(mutex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ByIdentity;
+    use crate::EasyMutex;
+    use std::collections::HashMap;
+
+    // The key hashes the mutex address, not its interior, so the lint is a false positive.
+    #[allow(clippy::mutable_key_type)]
+    #[test]
+    fn registry_keyed_by_identity() {
+        let sessions = EasyMutex::new(Vec::<u32>::new());
+        let users = EasyMutex::new(Vec::<u32>::new());
+
+        let mut names = HashMap::new();
+        names.insert(ByIdentity::from(sessions.clone()), "sessions");
+        names.insert(ByIdentity::from(users.clone()), "users");
+
+        // Same value, different identity: keys stay distinct and survive mutation.
+        sessions.update(|v| v.push(1));
+        assert_eq!(names[&ByIdentity(sessions.clone())], "sessions");
+        assert_eq!(names[&ByIdentity(users.clone())], "users");
+
+        let key = ByIdentity(sessions);
+        assert_eq!(key.read(), vec![1]);
+        assert!(key.clone() == key);
+    }
+}
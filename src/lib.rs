@@ -16,7 +16,58 @@
 
 #![deny(missing_docs)]
 #![doc = include_str!("../README.md")]
-use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+mod async_mutex;
+mod error;
+mod identity;
+mod lock_all;
+mod observer;
+mod policy;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+mod registry;
+mod rwlock;
+mod shared_cell;
+mod stats;
+mod stm;
+mod watch;
+mod weak;
+
+pub use async_mutex::EasyAsyncMutex;
+pub use error::EasyMutexError;
+pub use identity::ByIdentity;
+pub use lock_all::{LockAll, with_all, with_all_result};
+pub use observer::ObserverHandle;
+pub use policy::PoisonPolicy;
+#[cfg(feature = "prometheus")]
+pub use prometheus::render_prometheus;
+#[cfg(feature = "registry")]
+pub use registry::{LockInfo, registered_mutexes};
+pub use rwlock::EasyRwLock;
+pub use shared_cell::SharedCell;
+#[cfg(feature = "stats")]
+pub use stats::{LATENCY_BUCKETS, LockStats};
+pub use stm::{EasyTVar, Transaction, TxConflict, atomically};
+pub use watch::{EasyAsyncWatcher, EasyWatcher};
+pub use weak::EasyWeak;
+
+use observer::{Notify, Observers};
+use registry::{PoisonMark, Registered};
+use stats::{Hold, Recorder};
+use std::any;
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, LockResult, Mutex, MutexGuard, PoisonError, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
+use watch::WatchSender;
+
+/// Longest single sleep between two attempts of a timed lock acquisition.
+const MAX_BACKOFF: Duration = Duration::from_millis(1);
+
+/// Number of attempts that only yield the thread before a timed acquisition starts sleeping.
+const SPIN_ATTEMPTS: u32 = 16;
 
 /// A thread-safe, clonable wrapper around `std::sync::Mutex<T>` using `Arc`.
 ///
@@ -47,63 +98,1325 @@ use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 ///let data: EasyMutex<String> = "hello".to_string().into();
 ///assert_eq!(data.read(), "hello");
 /// ```
-#[derive(Clone, Default, Debug)]
-pub struct EasyMutex<T>(Arc<Mutex<T>>);
+#[derive(Default)]
+pub struct EasyMutex<T>(
+    Arc<Inner<T>>,
+    /// Counts this handle for the registry, which does not reference the shared state.
+    Registered,
+);
+
+/// State shared by every clone of an [`EasyMutex`].
+#[derive(Default)]
+struct Inner<T> {
+    /// Set by [`EasyMutex::named`], for debugging.
+    name: Option<String>,
+    policy: PoisonPolicy,
+    /// Number of writes so far, only modified while holding the lock.
+    version: AtomicU64,
+    mutex: Mutex<T>,
+    /// Notified after every write, for the `wait_` methods.
+    changed: Condvar,
+    /// Number of threads waiting on `changed`, only modified while holding the lock, so that
+    /// writes skip the notification when nobody waits.
+    waiters: AtomicUsize,
+    /// Notifies the [`EasyWatcher`]s after every write, and closes when the state is dropped.
+    watch: WatchSender,
+    /// Callbacks run after every write, once the lock is released.
+    observers: Arc<Observers<T>>,
+    /// Lock metrics, only recorded with the `stats` feature.
+    stats: Arc<Recorder>,
+    /// Set by [`EasyMutex::named`] with the `registry` feature.
+    registered: Registered,
+}
+
+/// An acquired lock, timing how long it is held with the `stats` feature.
+pub(crate) struct LockGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    _hold: Hold<'a>,
+    _poison: PoisonMark<'a>,
+}
+
+impl<'a, T> LockGuard<'a, T> {
+    /// Stops the timer and returns the underlying guard, e.g. to wait on a condition variable.
+    fn into_inner(self) -> MutexGuard<'a, T> {
+        self.guard
+    }
+}
+
+impl<T> Deref for LockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for LockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+/// Exclusive access to the value of an [`EasyMutex`] that counts as a write.
+///
+/// Every method that hands out `&mut T` goes through this guard, so that releasing it bumps
+/// the version, wakes the threads waiting for a change and then runs the observers. Nothing is
+/// recorded if the writer panics while holding the lock: the mutex is poisoned instead.
+pub(crate) struct WriteGuard<'a, T> {
+    inner: &'a Inner<T>,
+    /// Only `None` once released.
+    guard: Option<LockGuard<'a, T>>,
+    /// The value before the write, if observers are registered.
+    old: Option<T>,
+    /// Whether the thread was already panicking when the lock was acquired, in which case
+    /// `std` does not poison the mutex either.
+    panicking: bool,
+}
+
+impl<T> WriteGuard<'_, T> {
+    /// Releases the lock and returns the pending observer callbacks, so that a method holding
+    /// several locks can release all of them before any callback runs.
+    pub(crate) fn release(mut self) -> Notify<T> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Notify<T> {
+        let Some(guard) = self.guard.take() else {
+            return Notify::none();
+        };
+        if thread::panicking() && !self.panicking {
+            return Notify::none();
+        }
+        let version = self.inner.version.fetch_add(1, Ordering::Release) + 1;
+        if self.inner.waiters.load(Ordering::Relaxed) > 0 {
+            self.inner.changed.notify_all();
+        }
+        let wakers = self.inner.watch.publish(version);
+        self.inner
+            .observers
+            .after(self.old.take(), &guard)
+            .waking(wakers)
+    }
+}
+
+impl<T> Deref for WriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.guard.as_deref().unwrap()
+    }
+}
+
+impl<T> DerefMut for WriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.guard.as_deref_mut().unwrap()
+    }
+}
+
+impl<T> Drop for WriteGuard<'_, T> {
+    fn drop(&mut self) {
+        drop(self.finish());
+    }
+}
 
 impl<T> EasyMutex<T> {
     /// Creates a new `EasyMutex` wrapping the given value.
     ///
-    /// # Arguments
+    /// # Arguments
+    ///
+    /// * `value` - The value to wrap in a mutex.
+    ///
+    /// # Returns
+    ///
+    /// An `EasyMutex` instance holding the provided value.
+    pub fn new(value: T) -> Self {
+        Self::new_with_policy(value, PoisonPolicy::default())
+    }
+
+    /// Creates a new `EasyMutex` wrapping the given value, handling poison according to `policy`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to wrap in a mutex.
+    /// * `policy` - What every clone of the returned handle does when the lock is poisoned.
+    ///
+    /// # Returns
+    ///
+    /// An `EasyMutex` instance holding the provided value.
+    pub fn new_with_policy(value: T, policy: PoisonPolicy) -> Self {
+        Self::build(None, value, policy)
+    }
+
+    /// Creates a new `EasyMutex` wrapping the given value, with a name shown by its `Debug`
+    /// output.
+    ///
+    /// With the `registry` feature, named mutexes are also listed by `registered_mutexes()`
+    /// while they are alive.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A name identifying the mutex, which does not need to be unique.
+    /// * `value` - The value to wrap in a mutex.
+    ///
+    /// # Returns
+    ///
+    /// An `EasyMutex` instance holding the provided value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let sessions = EasyMutex::named("sessions", Vec::<u32>::new());
+    /// assert_eq!(sessions.name(), Some("sessions"));
+    /// assert_eq!(EasyMutex::new(0).name(), None);
+    /// ```
+    pub fn named(name: impl Into<String>, value: T) -> Self {
+        Self::named_with_policy(name, value, PoisonPolicy::default())
+    }
+
+    /// Same as [`EasyMutex::named`], handling poison according to `policy`.
+    pub fn named_with_policy(name: impl Into<String>, value: T, policy: PoisonPolicy) -> Self {
+        Self::build(Some(name.into()), value, policy)
+    }
+
+    fn build(name: Option<String>, value: T, policy: PoisonPolicy) -> Self {
+        let stats = Arc::default();
+        let registered = match &name {
+            Some(name) => Registered::new(name, any::type_name::<T>(), &stats),
+            None => Registered::default(),
+        };
+        Self::from_inner(Arc::new(Inner {
+            name,
+            policy,
+            version: AtomicU64::new(0),
+            mutex: Mutex::new(value),
+            changed: Condvar::new(),
+            waiters: AtomicUsize::new(0),
+            watch: WatchSender::default(),
+            observers: Arc::default(),
+            stats,
+            registered,
+        }))
+    }
+
+    /// Creates a handle to existing shared state.
+    pub(crate) fn from_inner(inner: Arc<Inner<T>>) -> Self {
+        let registered = inner.registered.clone();
+        Self(inner, registered)
+    }
+
+    /// Returns the name given to [`EasyMutex::named`], if any.
+    pub fn name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+
+    /// Returns the [`PoisonPolicy`] this mutex was created with.
+    pub fn poison_policy(&self) -> PoisonPolicy {
+        self.0.policy
+    }
+
+    /// Reads the inner value by acquiring a lock, cloning it and releasing it.
+    ///
+    /// # Returns
+    ///
+    /// A clone of the inner value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    pub fn read(&self) -> T
+    where
+        T: Clone,
+    {
+        self.lock().unwrap().clone()
+    }
+
+    /// Writes a new value into the mutex by acquiring a lock, replacing the inner value and releasing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_value` - The new value to be stored in the mutex.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    pub fn write(&self, new_value: T) {
+        *self.write_lock().unwrap() = new_value;
+    }
+
+    /// Same as [`EasyMutex::read`], but return a `Result<T, EasyMutexError>` type.
+    pub fn read_result(&self) -> Result<T, EasyMutexError>
+    where
+        T: Clone,
+    {
+        self.lock().map(|guard| guard.clone())
+    }
+
+    /// Same as [`EasyMutex::write`], but return a `Result<(), EasyMutexError>` type.
+    pub fn write_result(&self, new_value: T) -> Result<(), EasyMutexError> {
+        self.write_lock().map(|mut guard| *guard = new_value)
+    }
+
+    /// Runs `f` against a shared reference to the locked value and returns its output,
+    /// without cloning the whole value.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A closure receiving a reference to the inner value.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let list = EasyMutex::new(vec![1, 2, 3]);
+    /// assert_eq!(list.with(|v| v.len()), 3);
+    /// assert_eq!(list.with(|v| v[1]), 2);
+    /// ```
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.lock().unwrap())
+    }
+
+    /// Same as [`EasyMutex::with`], but return a `Result<R, EasyMutexError>` type.
+    pub fn with_result<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, EasyMutexError> {
+        self.lock().map(|guard| f(&guard))
+    }
+
+    /// Runs `f` against a mutable reference to the locked value and returns its output.
+    ///
+    /// This is the mutable counterpart of [`EasyMutex::with`] and behaves exactly like
+    /// [`EasyMutex::update`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        self.update(f)
+    }
+
+    /// Same as [`EasyMutex::with_mut`], but return a `Result<R, EasyMutexError>` type.
+    pub fn with_mut_result<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, EasyMutexError> {
+        self.update_result(f)
+    }
+
+    /// Updates the inner value in place by acquiring a lock, running `f` on it and releasing it.
+    ///
+    /// The whole read-modify-write happens under a single lock acquisition, so no other
+    /// thread can observe or modify the value in between.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A closure receiving a mutable reference to the inner value.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let counter = EasyMutex::new(1);
+    /// let previous = counter.update(|v| {
+    ///     let previous = *v;
+    ///     *v += 1;
+    ///     previous
+    /// });
+    /// assert_eq!(previous, 1);
+    /// assert_eq!(counter.read(), 2);
+    /// ```
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.write_lock().unwrap())
+    }
+
+    /// Same as [`EasyMutex::update`], but return a `Result<R, EasyMutexError>` type.
+    pub fn update_result<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, EasyMutexError> {
+        self.write_lock().map(|mut guard| f(&mut guard))
+    }
+
+    /// Writes a new value into the mutex and returns the previous one, under a single lock acquisition.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_value` - The new value to be stored in the mutex.
+    ///
+    /// # Returns
+    ///
+    /// The value that was stored before.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let shared = EasyMutex::new(1);
+    /// assert_eq!(shared.replace(2), 1);
+    /// assert_eq!(shared.take(), 2);
+    /// assert_eq!(shared.read(), 0);
+    /// ```
+    pub fn replace(&self, new_value: T) -> T {
+        self.replace_result(new_value).unwrap()
+    }
+
+    /// Same as [`EasyMutex::replace`], but return a `Result<T, EasyMutexError>` type.
+    pub fn replace_result(&self, new_value: T) -> Result<T, EasyMutexError> {
+        self.update_result(|value| mem::replace(value, new_value))
+    }
+
+    /// Takes the inner value, leaving `T::default()` in its place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        self.take_result().unwrap()
+    }
+
+    /// Same as [`EasyMutex::take`], but return a `Result<T, EasyMutexError>` type.
+    pub fn take_result(&self) -> Result<T, EasyMutexError>
+    where
+        T: Default,
+    {
+        self.update_result(mem::take)
+    }
+
+    /// Exchanges the values of two mutexes.
+    ///
+    /// Both locks are held during the exchange. They are always acquired in the same global
+    /// order (by address), so two threads swapping the same pair in opposite directions cannot
+    /// deadlock. Swapping a mutex with itself, or with a clone of itself, does nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let left = EasyMutex::new("left");
+    /// let right = EasyMutex::new("right");
+    /// left.swap(&right);
+    /// assert_eq!((left.read(), right.read()), ("right", "left"));
+    /// ```
+    pub fn swap(&self, other: &Self) {
+        self.swap_result(other).unwrap()
+    }
+
+    /// Same as [`EasyMutex::swap`], but return a `Result<(), EasyMutexError>` type.
+    pub fn swap_result(&self, other: &Self) -> Result<(), EasyMutexError> {
+        if self.ptr_eq(other) {
+            return Ok(());
+        }
+        let (first, second) = if self.addr() < other.addr() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        // Only count the swap as a write of either mutex once both locks are acquired.
+        let guards = (first.lock()?, second.lock()?);
+        let mut first = first.writing(guards.0);
+        let mut second = second.writing(guards.1);
+        mem::swap(&mut *first, &mut *second);
+        // Release both locks before running the observers of either mutex.
+        let _notify = (first.release(), second.release());
+        Ok(())
+    }
+
+    /// Writes `new_value` only if the current value equals `expected`, under a single lock acquisition.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the value was written, or `Err` with a clone of the value observed instead
+    /// of `expected`, in which case `new_value` is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let state = EasyMutex::new("idle");
+    /// assert_eq!(state.compare_and_set(&"idle", "running"), Ok(()));
+    /// assert_eq!(state.compare_and_set(&"idle", "running"), Err("running"));
+    /// ```
+    pub fn compare_and_set(&self, expected: &T, new_value: T) -> Result<(), T>
+    where
+        T: PartialEq + Clone,
+    {
+        self.compare_and_set_result(expected, new_value).unwrap()
+    }
+
+    /// Same as [`EasyMutex::compare_and_set`], but return a
+    /// `Result<Result<(), T>, EasyMutexError>` type.
+    pub fn compare_and_set_result(
+        &self,
+        expected: &T,
+        new_value: T,
+    ) -> Result<Result<(), T>, EasyMutexError>
+    where
+        T: PartialEq + Clone,
+    {
+        self.write_if_result(|current| current == expected, new_value)
+    }
+
+    /// Writes `new_value` only if `predicate` holds for the current value, under a single lock
+    /// acquisition.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the value was written, or `Err` with a clone of the value that failed the
+    /// predicate, in which case `new_value` is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    pub fn write_if(&self, predicate: impl FnOnce(&T) -> bool, new_value: T) -> Result<(), T>
+    where
+        T: Clone,
+    {
+        self.write_if_result(predicate, new_value).unwrap()
+    }
+
+    /// Same as [`EasyMutex::write_if`], but return a `Result<Result<(), T>, EasyMutexError>`
+    /// type.
+    pub fn write_if_result(
+        &self,
+        predicate: impl FnOnce(&T) -> bool,
+        new_value: T,
+    ) -> Result<Result<(), T>, EasyMutexError>
+    where
+        T: Clone,
+    {
+        self.update_if_result(predicate, |value| *value = new_value)
+    }
+
+    /// Runs `f` on the value only if `predicate` holds for it, under a single lock acquisition.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` with the output of `f`, or `Err` with a clone of the value that failed the predicate,
+    /// in which case `f` is not called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let stock = EasyMutex::new(1);
+    /// assert_eq!(stock.update_if(|n| *n > 0, |n| *n -= 1), Ok(()));
+    /// assert_eq!(stock.update_if(|n| *n > 0, |n| *n -= 1), Err(0));
+    /// ```
+    pub fn update_if<R>(
+        &self,
+        predicate: impl FnOnce(&T) -> bool,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, T>
+    where
+        T: Clone,
+    {
+        self.update_if_result(predicate, f).unwrap()
+    }
+
+    /// Same as [`EasyMutex::update_if`], but return a `Result<Result<R, T>, EasyMutexError>`
+    /// type.
+    pub fn update_if_result<R>(
+        &self,
+        predicate: impl FnOnce(&T) -> bool,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<Result<R, T>, EasyMutexError>
+    where
+        T: Clone,
+    {
+        let guard = self.lock()?;
+        if !predicate(&guard) {
+            return Ok(Err(guard.clone()));
+        }
+        Ok(Ok(f(&mut self.writing(guard))))
+    }
+
+    /// Returns the number of writes made to the mutex so far.
+    ///
+    /// Every method that can modify the value counts as a write, even if it leaves the value
+    /// unchanged; conditional writes that do not happen are not counted.
+    pub fn version(&self) -> u64 {
+        self.0.version.load(Ordering::Acquire)
+    }
+
+    /// Reads the inner value together with its [`EasyMutex::version`], under a single lock
+    /// acquisition.
+    ///
+    /// Pass the version to [`EasyMutex::write_if_version`] later to update the value only if
+    /// nobody wrote in between, without holding the lock meanwhile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let profile = EasyMutex::new(String::from("alice"));
+    /// let (name, etag) = profile.read_versioned();
+    ///
+    /// // Slow work without holding the lock...
+    /// let renamed = name.to_uppercase();
+    ///
+    /// assert_eq!(profile.write_if_version(etag, renamed), Ok(etag + 1));
+    /// assert_eq!(profile.write_if_version(etag, String::from("bob")), Err(etag + 1));
+    /// assert_eq!(profile.read(), "ALICE");
+    /// ```
+    pub fn read_versioned(&self) -> (T, u64)
+    where
+        T: Clone,
+    {
+        self.read_versioned_result().unwrap()
+    }
+
+    /// Same as [`EasyMutex::read_versioned`], but return a `Result<(T, u64), EasyMutexError>` type.
+    pub fn read_versioned_result(&self) -> Result<(T, u64), EasyMutexError>
+    where
+        T: Clone,
+    {
+        self.lock()
+            .map(|guard| (guard.clone(), self.0.version.load(Ordering::Acquire)))
+    }
+
+    /// Writes `new_value` only if the [`EasyMutex::version`] is still `version`, under a single
+    /// lock acquisition.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` with the new version if the value was written, or `Err` with the current version if
+    /// someone wrote in between, in which case `new_value` is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    pub fn write_if_version(&self, version: u64, new_value: T) -> Result<u64, u64> {
+        self.write_if_version_result(version, new_value).unwrap()
+    }
+
+    /// Same as [`EasyMutex::write_if_version`], but return a
+    /// `Result<Result<u64, u64>, EasyMutexError>` type.
+    pub fn write_if_version_result(
+        &self,
+        version: u64,
+        new_value: T,
+    ) -> Result<Result<u64, u64>, EasyMutexError> {
+        let guard = self.lock()?;
+        let current = self.0.version.load(Ordering::Acquire);
+        if current != version {
+            return Ok(Err(current));
+        }
+        *self.writing(guard) = new_value;
+        Ok(Ok(current + 1))
+    }
+
+    /// Blocks the current thread until `predicate` holds for the value, then returns a clone of it.
+    ///
+    /// The predicate is checked right away, then again after every write, without polling:
+    /// the lock is released while waiting.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    /// use std::thread;
+    ///
+    /// let progress = EasyMutex::new(0);
+    /// let worker = progress.clone();
+    /// let handle = thread::spawn(move || {
+    ///     for _ in 0..3 {
+    ///         worker.update(|p| *p += 1);
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(progress.wait_until(|p| *p == 3), 3);
+    /// handle.join().unwrap();
+    /// ```
+    pub fn wait_until(&self, predicate: impl FnMut(&T) -> bool) -> T
+    where
+        T: Clone,
+    {
+        self.wait_until_result(predicate).unwrap()
+    }
+
+    /// Same as [`EasyMutex::wait_until`], but return a `Result<T, EasyMutexError>` type.
+    pub fn wait_until_result(&self, predicate: impl FnMut(&T) -> bool) -> Result<T, EasyMutexError>
+    where
+        T: Clone,
+    {
+        self.wait_locked(self.lock()?, predicate)
+            .map(|guard| guard.clone())
+    }
+
+    /// Same as [`EasyMutex::wait_until`], but gives up once `timeout` has elapsed.
+    ///
+    /// Returns `Err(EasyMutexError::TimedOut)` if the predicate still does not hold after
+    /// `timeout`, or `Err(EasyMutexError::Poisoned)` if the mutex is poisoned.
+    pub fn wait_until_timeout(
+        &self,
+        mut predicate: impl FnMut(&T) -> bool,
+        timeout: Duration,
+    ) -> Result<T, EasyMutexError>
+    where
+        T: Clone,
+    {
+        // A timeout too large to be represented never elapses.
+        let Some(deadline) = Instant::now().checked_add(timeout) else {
+            return self.wait_until_result(predicate);
+        };
+        let mut guard = self.lock()?;
+        while !predicate(&guard) {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(EasyMutexError::TimedOut);
+            }
+            guard = self.wait_changed(guard, Some(deadline - now))?;
+        }
+        Ok(guard.clone())
+    }
+
+    /// Blocks the current thread until the [`EasyMutex::version`] is greater than `since`.
+    ///
+    /// # Returns
+    ///
+    /// A clone of the value and its version, to pass to the next call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock),
+    /// unless its [`PoisonPolicy`] recovers from it.
+    pub fn wait_for_change(&self, since: u64) -> (T, u64)
+    where
+        T: Clone,
+    {
+        self.wait_for_change_result(since).unwrap()
+    }
+
+    /// Same as [`EasyMutex::wait_for_change`], but return a `Result<(T, u64), EasyMutexError>`
+    /// type.
+    pub fn wait_for_change_result(&self, since: u64) -> Result<(T, u64), EasyMutexError>
+    where
+        T: Clone,
+    {
+        let guard = self.wait_locked(self.lock()?, |_| {
+            self.0.version.load(Ordering::Acquire) > since
+        })?;
+        Ok((guard.clone(), self.0.version.load(Ordering::Acquire)))
+    }
+
+    /// Reads the inner value even if the mutex is poisoned.
+    ///
+    /// This is the way to recover the data after an [`EasyMutexError::Poisoned`]: the poison
+    /// flag is left untouched, so the other methods keep reporting it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::{EasyMutex, EasyMutexError};
+    ///
+    /// let shared = EasyMutex::new(1);
+    /// let poisoner = shared.clone();
+    /// let _ = std::thread::spawn(move || poisoner.update(|_| panic!("boom"))).join();
+    ///
+    /// assert_eq!(shared.read_result(), Err(EasyMutexError::Poisoned));
+    /// assert_eq!(shared.read_poisoned(), 1);
+    /// ```
+    pub fn read_poisoned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.lock_poisoned().clone()
+    }
+
+    /// Returns `true` if another thread panicked while holding the lock and the poison flag
+    /// has not been cleared since.
+    pub fn is_poisoned(&self) -> bool {
+        self.0.mutex.is_poisoned()
+    }
+
+    /// Clears the poison flag, leaving the value exactly as the panicking thread left it.
+    ///
+    /// Prefer [`EasyMutex::recover_with`] when the value may have been left half-updated.
+    pub fn clear_poison(&self) {
+        self.0.mutex.clear_poison();
+        self.0.registered.clear_poison();
+    }
+
+    /// Repairs a possibly poisoned mutex.
+    ///
+    /// Acquires the lock regardless of poisoning, lets `f` validate or fix the value,
+    /// then clears the poison flag. If `f` panics, the mutex stays poisoned.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A closure receiving a mutable reference to the inner value.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `f`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let balances = EasyMutex::new(vec![10, 20]);
+    /// let poisoner = balances.clone();
+    /// let _ = std::thread::spawn(move || {
+    ///     poisoner.update(|v| {
+    ///         v[0] -= 5;
+    ///         panic!("crashed before crediting v[1]");
+    ///     })
+    /// })
+    /// .join();
+    ///
+    /// assert!(balances.is_poisoned());
+    /// balances.recover_with(|v| v[0] += 5);
+    /// assert!(!balances.is_poisoned());
+    /// assert_eq!(balances.read(), vec![10, 20]);
+    /// ```
+    pub fn recover_with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut guard = self.writing(self.lock_poisoned());
+        let result = f(&mut guard);
+        self.clear_poison();
+        result
+    }
+
+    /// Returns the inner value if this is the last handle to the mutex, or gives the handle back.
+    ///
+    /// The poison flag is ignored: the value is returned as the last writer left it.
+    /// Use [`EasyMutex::into_inner`] to have poisoning handled by the [`PoisonPolicy`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let shared = EasyMutex::new(String::from("owned"));
+    /// let clone = shared.clone();
+    ///
+    /// let shared = shared.try_unwrap().unwrap_err();
+    /// drop(clone);
+    /// assert_eq!(shared.try_unwrap().ok(), Some(String::from("owned")));
+    /// ```
+    pub fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self.0)
+            .map(|inner| {
+                inner
+                    .mutex
+                    .into_inner()
+                    .unwrap_or_else(PoisonError::into_inner)
+            })
+            .map_err(Self::from_inner)
+    }
+
+    /// Consumes the handle and returns the inner value if it was the last one.
+    ///
+    /// Like `Arc::into_inner`, this returns `None` when other handles still exist, and the
+    /// handle is dropped; if several handles are consumed concurrently, exactly one gets the
+    /// value. Poisoning is handled according to the [`PoisonPolicy`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let shared = EasyMutex::new(vec![1, 2]);
+    /// let clone = shared.clone();
+    ///
+    /// assert_eq!(clone.into_inner(), None);
+    /// assert_eq!(shared.into_inner(), Some(Ok(vec![1, 2])));
+    /// ```
+    pub fn into_inner(self) -> Option<Result<T, EasyMutexError>> {
+        let policy = self.0.policy;
+        Arc::into_inner(self.0).map(|inner| {
+            inner
+                .mutex
+                .into_inner()
+                .or_else(|err| policy.apply(err, || {}))
+        })
+    }
+
+    /// Returns a mutable reference to the inner value if this is the only handle to the mutex.
+    ///
+    /// No locking is needed since the handle is borrowed mutably. The poison flag is ignored,
+    /// as for [`EasyMutex::try_unwrap`]. Borrowing the value counts as a write for
+    /// [`EasyMutex::version`].
+    ///
+    /// Weak references also prevent it, so this returns `None` while an [`EasyWeak`] handle or
+    /// an [`EasyWatcher`] of this mutex is alive. [`ObserverHandle`]s and the registry of the
+    /// `registry` feature do not count.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(&mut self.0).map(|inner| {
+            *inner.version.get_mut() += 1;
+            inner
+                .mutex
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+        })
+    }
+
+    /// Returns `true` if both handles point to the same mutex, whatever the values inside.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    ///
+    /// let a = EasyMutex::new(1);
+    /// let b = EasyMutex::new(1);
+    /// assert!(a.ptr_eq(&a.clone()));
+    /// assert!(!a.ptr_eq(&b));
+    /// ```
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Returns the number of handles sharing this mutex, including this one.
+    pub fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.0)
+    }
+
+    /// Returns the number of [`EasyWeak`] handles to this mutex.
+    ///
+    /// The weak references kept by its [`EasyWatcher`]s are not counted.
+    pub fn weak_count(&self) -> usize {
+        // Saturating, since watchers take and drop their two references one at a time.
+        Arc::weak_count(&self.0).saturating_sub(self.0.watch.watchers())
+    }
+
+    /// Creates a non-owning [`EasyWeak`] handle to this mutex.
+    pub fn downgrade(&self) -> EasyWeak<T> {
+        EasyWeak(Arc::downgrade(&self.0))
+    }
+
+    /// Subscribes to the writes of this mutex.
+    ///
+    /// The returned [`EasyWatcher`] has already seen the current value, and its
+    /// [`EasyWatcher::changed`] blocks until the next write. Like [`EasyMutex::downgrade`], the
+    /// watcher does not keep the mutex alive.
     ///
-    /// * `value` - The value to wrap in a mutex.
+    /// # Example
     ///
-    /// # Returns
+    /// ```
+    /// use easy_mutex::EasyMutex;
     ///
-    /// An `EasyMutex` instance holding the provided value.
-    pub fn new(value: T) -> Self {
-        Self(Arc::new(Mutex::new(value)))
+    /// let status = EasyMutex::new("idle");
+    /// let mut watcher = status.subscribe();
+    ///
+    /// status.write("busy");
+    /// status.write("done");
+    /// watcher.changed().unwrap();
+    /// assert_eq!(watcher.read(), Some("done"));
+    /// ```
+    pub fn subscribe(&self) -> EasyWatcher<T> {
+        EasyWatcher::attach(self)
     }
 
-    /// Reads the inner value by acquiring a lock, cloning it and releasing it.
+    /// Registers a callback run with the old and new value after every write to this mutex.
+    ///
+    /// The callback runs on the writing thread once the lock is released, so it may lock this
+    /// mutex or others; it receives snapshots cloned at the time of the write. Conditional
+    /// writes whose predicate fails do not call it.
     ///
     /// # Returns
     ///
-    /// A clone of the inner value.
+    /// An [`ObserverHandle`] that unregisters the callback when dropped. Like
+    /// [`EasyMutex::downgrade`], it does not keep the mutex alive.
     ///
-    /// # Panics
+    /// # Example
     ///
-    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock).
-    pub fn read(&self) -> T
+    /// ```
+    /// use easy_mutex::EasyMutex;
+    /// use std::sync::mpsc;
+    ///
+    /// let balance = EasyMutex::new(100);
+    /// let (audit, log) = mpsc::channel();
+    /// let handle = balance.on_change(move |old, new| audit.send((*old, *new)).unwrap());
+    ///
+    /// balance.update(|b| *b -= 30);
+    /// assert_eq!(log.try_recv(), Ok((100, 70)));
+    ///
+    /// drop(handle);
+    /// balance.write(0);
+    /// assert!(log.try_recv().is_err());
+    /// ```
+    pub fn on_change(&self, f: impl Fn(&T, &T) + Send + Sync + 'static) -> ObserverHandle<T>
     where
         T: Clone,
     {
-        self.0.lock().unwrap().clone()
+        let id = self.0.observers.register(T::clone, Arc::new(f));
+        ObserverHandle::new(&self.0.observers, id)
     }
 
-    /// Writes a new value into the mutex by acquiring a lock, replacing the inner value and releasing it.
+    /// Same as [`EasyMutex::subscribe`], but returns an [`EasyAsyncWatcher`] whose
+    /// `changed()` and `next()` are awaited instead of blocking.
+    pub fn subscribe_async(&self) -> EasyAsyncWatcher<T> {
+        EasyAsyncWatcher::attach(self)
+    }
+
+    /// Returns the lock metrics recorded since the mutex was created or
+    /// [`EasyMutex::reset_stats`] was last called.
     ///
-    /// # Arguments
+    /// Requires the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> LockStats {
+        self.0.stats.snapshot()
+    }
+
+    /// Resets every lock metric to zero.
     ///
-    /// * `new_value` - The new value to be stored in the mutex.
+    /// The counters exported by `render_prometheus` are not affected: they keep the
+    /// metrics reset, so that they never go down.
     ///
-    /// # Panics
+    /// Requires the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn reset_stats(&self) {
+        self.0.registered.reset_stats(&self.0.stats);
+    }
+
+    /// Address of the shared state, identifying the mutex across all its handles.
+    pub(crate) fn addr(&self) -> usize {
+        Arc::as_ptr(&self.0).addr()
+    }
+
+    /// Attempts to read the inner value without blocking.
     ///
-    /// Panics if the mutex is poisoned (e.g., another thread panicked while holding the lock).
-    pub fn write(&self, new_value: T) {
-        *self.0.lock().unwrap() = new_value;
+    /// # Returns
+    ///
+    /// A clone of the inner value, `Err(EasyMutexError::WouldBlock)` if the lock is currently
+    /// held elsewhere, or `Err(EasyMutexError::Poisoned)` if the mutex is poisoned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::{EasyMutex, EasyMutexError};
+    ///
+    /// let shared = EasyMutex::new(5);
+    /// assert_eq!(shared.try_read(), Ok(5));
+    ///
+    /// shared.with(|_| {
+    ///     assert_eq!(shared.try_read(), Err(EasyMutexError::WouldBlock));
+    /// });
+    /// ```
+    pub fn try_read(&self) -> Result<T, EasyMutexError>
+    where
+        T: Clone,
+    {
+        self.try_lock().map(|guard| guard.clone())
+    }
+
+    /// Attempts to write a new value without blocking.
+    ///
+    /// Returns `Err(EasyMutexError::WouldBlock)` if the lock is currently held elsewhere,
+    /// in which case `new_value` is dropped.
+    pub fn try_write(&self, new_value: T) -> Result<(), EasyMutexError> {
+        self.try_write_lock().map(|mut guard| *guard = new_value)
+    }
+
+    /// Attempts to update the inner value in place without blocking.
+    ///
+    /// Returns `Err(EasyMutexError::WouldBlock)` if the lock is currently held elsewhere,
+    /// in which case `f` is not called.
+    pub fn try_update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, EasyMutexError> {
+        self.try_write_lock().map(|mut guard| f(&mut guard))
     }
 
-    /// Same as [`EasyMutex::read`], but return a `Result<T, PoisonError<MutexGuard<'_, T>>>` type.
-    pub fn read_result(&self) -> Result<T, PoisonError<MutexGuard<'_, T>>>
+    /// Reads the inner value, waiting at most `timeout` for the lock.
+    ///
+    /// # Returns
+    ///
+    /// A clone of the inner value, `Err(EasyMutexError::TimedOut)` if the lock could not be
+    /// acquired before `timeout` elapsed, or `Err(EasyMutexError::Poisoned)` if the mutex is poisoned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use easy_mutex::{EasyMutex, EasyMutexError};
+    /// use std::time::Duration;
+    ///
+    /// let shared = EasyMutex::new(5);
+    /// assert_eq!(shared.read_timeout(Duration::from_millis(10)), Ok(5));
+    ///
+    /// shared.with(|_| {
+    ///     let timed_out = shared.read_timeout(Duration::from_millis(10));
+    ///     assert_eq!(timed_out, Err(EasyMutexError::TimedOut));
+    /// });
+    /// ```
+    pub fn read_timeout(&self, timeout: Duration) -> Result<T, EasyMutexError>
     where
         T: Clone,
     {
-        self.0.lock().map(|guard| guard.clone())
+        self.lock_timeout(timeout).map(|guard| guard.clone())
+    }
+
+    /// Writes a new value, waiting at most `timeout` for the lock.
+    ///
+    /// Returns `Err(EasyMutexError::TimedOut)` if the lock could not be acquired in time,
+    /// in which case `new_value` is dropped.
+    pub fn write_timeout(&self, new_value: T, timeout: Duration) -> Result<(), EasyMutexError> {
+        self.write_lock_timeout(timeout)
+            .map(|mut guard| *guard = new_value)
+    }
+
+    /// Updates the inner value in place, waiting at most `timeout` for the lock.
+    ///
+    /// Returns `Err(EasyMutexError::TimedOut)` if the lock could not be acquired in time,
+    /// in which case `f` is not called.
+    pub fn update_timeout<R>(
+        &self,
+        f: impl FnOnce(&mut T) -> R,
+        timeout: Duration,
+    ) -> Result<R, EasyMutexError> {
+        self.write_lock_timeout(timeout)
+            .map(|mut guard| f(&mut guard))
+    }
+
+    /// Acquires the lock, blocking the current thread until it is available.
+    fn lock(&self) -> Result<LockGuard<'_, T>, EasyMutexError> {
+        match self.try_acquire(None) {
+            Err(EasyMutexError::WouldBlock) => {
+                let start = Instant::now();
+                let result = self.0.mutex.lock();
+                self.acquired(result, Some(start.elapsed()))
+            }
+            result => result,
+        }
+    }
+
+    /// Attempts to acquire the lock without blocking.
+    fn try_lock(&self) -> Result<LockGuard<'_, T>, EasyMutexError> {
+        self.try_acquire(None)
+    }
+
+    /// Attempts to acquire the lock without blocking, after waiting for it since `since`.
+    fn try_acquire(&self, since: Option<Instant>) -> Result<LockGuard<'_, T>, EasyMutexError> {
+        let result = match self.0.mutex.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::Poisoned(err)) => Err(err),
+            Err(TryLockError::WouldBlock) => return Err(EasyMutexError::WouldBlock),
+        };
+        self.acquired(result, since.map(|since| since.elapsed()))
+    }
+
+    /// Acquires the lock whether it is poisoned or not, recording it as [`EasyMutex::lock`]
+    /// does but ignoring the [`PoisonPolicy`].
+    fn lock_poisoned(&self) -> LockGuard<'_, T> {
+        let (result, wait) = match self.0.mutex.try_lock() {
+            Ok(guard) => (Ok(guard), None),
+            Err(TryLockError::Poisoned(err)) => (Err(err), None),
+            Err(TryLockError::WouldBlock) => {
+                let start = Instant::now();
+                (self.0.mutex.lock(), Some(start.elapsed()))
+            }
+        };
+        self.0.stats.acquired(wait);
+        let guard = result.unwrap_or_else(|err| {
+            self.0.stats.poisoned();
+            err.into_inner()
+        });
+        self.held(guard)
+    }
+
+    /// Records an acquisition that waited for `wait` if contended, and applies the
+    /// [`PoisonPolicy`].
+    fn acquired<'a>(
+        &'a self,
+        result: LockResult<MutexGuard<'a, T>>,
+        wait: Option<Duration>,
+    ) -> Result<LockGuard<'a, T>, EasyMutexError> {
+        self.0.stats.acquired(wait);
+        result
+            .or_else(|err| self.on_poison(err))
+            .map(|guard| self.held(guard))
+    }
+
+    /// Starts timing how long `guard` is held.
+    fn held<'a>(&'a self, guard: MutexGuard<'a, T>) -> LockGuard<'a, T> {
+        LockGuard {
+            guard,
+            _hold: self.0.stats.hold(),
+            _poison: self.0.registered.mark(),
+        }
+    }
+
+    /// Acquires the lock, giving up once `timeout` has elapsed.
+    ///
+    /// `std::sync::Mutex` has no timed lock, so this polls `try_lock`: it first yields the
+    /// thread a few times, then sleeps with an exponential backoff capped at [`MAX_BACKOFF`]
+    /// and never past the deadline.
+    fn lock_timeout(&self, timeout: Duration) -> Result<LockGuard<'_, T>, EasyMutexError> {
+        let start = Instant::now();
+        // A timeout too large to be represented never elapses.
+        let Some(deadline) = start.checked_add(timeout) else {
+            return self.lock();
+        };
+        let mut backoff = Duration::from_micros(10);
+        let mut attempts = 0;
+        loop {
+            match self.try_acquire((attempts > 0).then_some(start)) {
+                Err(EasyMutexError::WouldBlock) => {}
+                result => return result,
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(EasyMutexError::TimedOut);
+            }
+            if attempts < SPIN_ATTEMPTS {
+                attempts += 1;
+                thread::yield_now();
+            } else {
+                thread::sleep(backoff.min(deadline - now));
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+
+    /// Waits on the condition variable, starting from an acquired lock, until `predicate` holds.
+    fn wait_locked<'a>(
+        &'a self,
+        mut guard: LockGuard<'a, T>,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> Result<LockGuard<'a, T>, EasyMutexError> {
+        while !predicate(&guard) {
+            guard = self.wait_changed(guard, None)?;
+        }
+        Ok(guard)
+    }
+
+    /// Releases the lock until the next write or, if given, until `timeout` has elapsed, then
+    /// acquires it again.
+    fn wait_changed<'a>(
+        &'a self,
+        guard: LockGuard<'a, T>,
+        timeout: Option<Duration>,
+    ) -> Result<LockGuard<'a, T>, EasyMutexError> {
+        self.0.waiters.fetch_add(1, Ordering::Relaxed);
+        let raw = guard.into_inner();
+        let raw = match timeout {
+            Some(timeout) => self
+                .0
+                .changed
+                .wait_timeout(raw, timeout)
+                .map(|(raw, _)| raw)
+                .map_err(|err| PoisonError::new(err.into_inner().0)),
+            None => self.0.changed.wait(raw),
+        };
+        // The lock is held again, poisoned or not.
+        self.0.waiters.fetch_sub(1, Ordering::Relaxed);
+        let raw = raw.or_else(|err| self.on_poison(err))?;
+        Ok(self.held(raw))
     }
 
-    /// Same as [`EasyMutex::write`], but return a `Result<(), PoisonError<MutexGuard<'_, T>>>` type.
-    pub fn write_result(&self, new_value: T) -> Result<(), PoisonError<MutexGuard<'_, T>>> {
-        self.0.lock().map(|mut guard| *guard = new_value)
+    /// Acquires the lock for a write, blocking the current thread until it is available.
+    fn write_lock(&self) -> Result<WriteGuard<'_, T>, EasyMutexError> {
+        self.lock().map(|guard| self.writing(guard))
+    }
+
+    /// Attempts to acquire the lock for a write without blocking.
+    fn try_write_lock(&self) -> Result<WriteGuard<'_, T>, EasyMutexError> {
+        self.try_lock().map(|guard| self.writing(guard))
+    }
+
+    /// Acquires the lock for a write, giving up once `timeout` has elapsed.
+    fn write_lock_timeout(&self, timeout: Duration) -> Result<WriteGuard<'_, T>, EasyMutexError> {
+        self.lock_timeout(timeout).map(|guard| self.writing(guard))
+    }
+
+    /// Turns an acquired lock into a [`WriteGuard`].
+    fn writing<'a>(&'a self, guard: LockGuard<'a, T>) -> WriteGuard<'a, T> {
+        WriteGuard {
+            inner: &self.0,
+            old: self.0.observers.before(&guard),
+            guard: Some(guard),
+            panicking: thread::panicking(),
+        }
+    }
+
+    /// Applies the [`PoisonPolicy`] to a poisoned acquisition.
+    fn on_poison<G>(&self, err: PoisonError<G>) -> Result<G, EasyMutexError> {
+        self.0.stats.poisoned();
+        self.0.policy.apply(err, || self.clear_poison())
+    }
+}
+
+/// Clones the handle, not the value: every clone shares the same mutex.
+impl<T> Clone for EasyMutex<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0), self.1.clone())
+    }
+}
+
+/// Shows the value without blocking: `<locked>` is printed if another thread holds the lock.
+impl<T: fmt::Debug> fmt::Debug for EasyMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("EasyMutex");
+        if let Some(name) = self.name() {
+            d.field("name", &name);
+        }
+        // Formatting the value may panic while holding the lock.
+        let _poison = self.0.registered.mark();
+        match self.0.mutex.try_lock() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(TryLockError::Poisoned(err)) => d.field("data", &&**err.get_ref()),
+            Err(TryLockError::WouldBlock) => d.field("data", &format_args!("<locked>")),
+        };
+        d.field("poisoned", &self.is_poisoned())
+            .field("policy", &self.0.policy)
+            .field("handles", &self.strong_count())
+            .finish()
     }
 }
 
@@ -116,10 +1429,21 @@ impl<T> From<T> for EasyMutex<T> {
 
 #[cfg(test)]
 mod tests {
-    use super::EasyMutex;
+    use super::{EasyMutex, EasyMutexError, PoisonPolicy, with_all_result};
+    use std::error::Error;
+    use std::mem;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, mpsc};
     use std::thread;
     use std::time::{Duration, Instant};
 
+    /// Poisons `m` by panicking in another thread while holding its lock.
+    fn poison<T: Send + 'static>(m: &EasyMutex<T>) {
+        let poisoner = m.clone();
+        let _ = thread::spawn(move || poisoner.update(|_| panic!("poison"))).join();
+    }
+
     #[test]
     fn basic_read_write() {
         let m = EasyMutex::new(10);
@@ -143,6 +1467,526 @@ mod tests {
         assert_eq!(val, 2);
     }
 
+    #[test]
+    fn borrowing_accessors() {
+        let m = EasyMutex::new(vec![1, 2, 3]);
+        assert_eq!(m.with(|v| v.iter().sum::<i32>()), 6);
+
+        m.with_mut(|v| v.push(4));
+        assert_eq!(m.with_result(|v| v.len()).unwrap(), 4);
+
+        let popped = m.with_mut_result(|v| v.pop()).unwrap();
+        assert_eq!(popped, Some(4));
+        assert_eq!(m.read(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn try_methods_do_not_block() {
+        let m = EasyMutex::new(1);
+        assert!(m.try_write(2).is_ok());
+        assert_eq!(m.try_update(|v| *v + 1).unwrap(), 3);
+        assert_eq!(m.try_read().unwrap(), 2);
+
+        m.with(|_| {
+            assert_eq!(m.try_read(), Err(EasyMutexError::WouldBlock));
+            assert_eq!(m.try_write(5), Err(EasyMutexError::WouldBlock));
+            assert_eq!(m.try_update(|v| *v = 5), Err(EasyMutexError::WouldBlock));
+        });
+        assert_eq!(m.read(), 2);
+    }
+
+    #[test]
+    fn timed_methods_time_out_while_locked() {
+        let m = EasyMutex::new(1);
+        let timeout = Duration::from_millis(20);
+        m.with(|_| {
+            let start = Instant::now();
+            assert_eq!(m.read_timeout(timeout), Err(EasyMutexError::TimedOut));
+            assert!(start.elapsed() >= timeout);
+            assert_eq!(m.write_timeout(2, timeout), Err(EasyMutexError::TimedOut));
+            assert_eq!(
+                m.update_timeout(|v| *v = 3, timeout),
+                Err(EasyMutexError::TimedOut)
+            );
+        });
+        assert_eq!(m.read(), 1);
+    }
+
+    #[test]
+    fn timed_methods_wait_for_release() {
+        let m = EasyMutex::new(1);
+        let holder = m.clone();
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            holder.update(|v| {
+                locked_tx.send(()).unwrap();
+                thread::sleep(Duration::from_millis(50));
+                *v = 2;
+            });
+        });
+
+        locked_rx.recv().unwrap();
+        assert_eq!(m.read_timeout(Duration::from_secs(10)).unwrap(), 2);
+        assert!(m.write_timeout(3, Duration::from_secs(10)).is_ok());
+        assert_eq!(
+            m.update_timeout(|v| *v + 1, Duration::from_secs(10))
+                .unwrap(),
+            4
+        );
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn timed_methods_accept_unbounded_timeouts() {
+        let m = EasyMutex::new(1);
+        let holder = m.clone();
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            holder.update(|v| {
+                locked_tx.send(()).unwrap();
+                thread::sleep(Duration::from_millis(20));
+                *v = 2;
+            });
+        });
+
+        locked_rx.recv().unwrap();
+        assert_eq!(m.read_timeout(Duration::MAX), Ok(2));
+        assert_eq!(m.write_timeout(3, Duration::MAX), Ok(()));
+        assert_eq!(m.update_timeout(|v| *v + 1, Duration::MAX), Ok(4));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn poisoned_mutex_reports_owned_error() {
+        let m = EasyMutex::new(7);
+        assert_eq!(m.poison_policy(), PoisonPolicy::Error);
+        poison(&m);
+
+        assert_eq!(m.read_result(), Err(EasyMutexError::Poisoned));
+        assert_eq!(m.write_result(8), Err(EasyMutexError::Poisoned));
+        assert_eq!(m.try_read(), Err(EasyMutexError::Poisoned));
+        assert_eq!(
+            m.read_timeout(Duration::from_millis(10)),
+            Err(EasyMutexError::Poisoned)
+        );
+        assert_eq!(m.read_poisoned(), 7);
+
+        let boxed: Box<dyn Error + Send + Sync + 'static> = m.read_result().unwrap_err().into();
+        assert!(boxed.to_string().contains("poisoned"));
+    }
+
+    #[test]
+    fn recover_policy_keeps_working_after_poison() {
+        let m = EasyMutex::new_with_policy(1, PoisonPolicy::Recover);
+        poison(&m);
+
+        assert_eq!(m.read(), 1);
+        m.write(2);
+        assert_eq!(m.update_result(|v| *v + 1), Ok(3));
+        assert!(m.is_poisoned());
+    }
+
+    #[test]
+    fn recover_and_clear_policy_clears_poison() {
+        let m = EasyMutex::new_with_policy(1, PoisonPolicy::RecoverAndClear);
+        poison(&m);
+        assert!(m.is_poisoned());
+
+        assert_eq!(m.read(), 1);
+        assert!(!m.is_poisoned());
+    }
+
+    #[test]
+    fn panic_policy_panics_even_for_result_methods() {
+        let m = EasyMutex::new_with_policy(1, PoisonPolicy::Panic);
+        poison(&m);
+
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| m.read_result())).is_err());
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| m.try_read())).is_err());
+        assert_eq!(m.read_poisoned(), 1);
+    }
+
+    #[test]
+    fn poison_inspection_and_recovery() {
+        let m = EasyMutex::new(vec![1, 2, 3]);
+        assert!(!m.is_poisoned());
+
+        poison(&m);
+        assert!(m.is_poisoned());
+        m.clear_poison();
+        assert!(!m.is_poisoned());
+        assert_eq!(m.read(), vec![1, 2, 3]);
+
+        poison(&m);
+        let len = m.recover_with(|v| {
+            v.push(4);
+            v.len()
+        });
+        assert_eq!(len, 4);
+        assert!(!m.is_poisoned());
+        assert_eq!(m.read_result(), Ok(vec![1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn recover_with_stays_poisoned_if_repair_panics() {
+        let m = EasyMutex::new(0);
+        poison(&m);
+
+        let repaired = panic::catch_unwind(AssertUnwindSafe(|| {
+            m.recover_with(|_| panic!("repair failed"))
+        }));
+        assert!(repaired.is_err());
+        assert!(m.is_poisoned());
+    }
+
+    #[test]
+    fn ownership_extraction_requires_last_handle() {
+        let mut m = EasyMutex::new(vec![1]);
+        m.get_mut().unwrap().push(2);
+
+        let clone = m.clone();
+        assert!(m.get_mut().is_none());
+        let m = m.try_unwrap().unwrap_err();
+        assert_eq!(clone.into_inner(), None);
+
+        assert_eq!(m.try_unwrap().ok(), Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn into_inner_follows_poison_policy() {
+        let m = EasyMutex::new(1);
+        poison(&m);
+        assert_eq!(m.into_inner(), Some(Err(EasyMutexError::Poisoned)));
+
+        let m = EasyMutex::new_with_policy(2, PoisonPolicy::Recover);
+        poison(&m);
+        assert_eq!(m.into_inner(), Some(Ok(2)));
+
+        let m = EasyMutex::new(3);
+        poison(&m);
+        assert_eq!(m.try_unwrap().ok(), Some(3));
+    }
+
+    #[test]
+    fn handle_identity_and_counts() {
+        let m = EasyMutex::new(1);
+        assert_eq!(m.strong_count(), 1);
+        assert_eq!(m.weak_count(), 0);
+
+        let clone = m.clone();
+        assert!(m.ptr_eq(&clone));
+        assert!(!m.ptr_eq(&EasyMutex::new(1)));
+        assert_eq!(m.strong_count(), 2);
+        drop(clone);
+        assert_eq!(m.strong_count(), 1);
+    }
+
+    #[test]
+    fn only_weak_handles_are_weak_references() {
+        let mut m = EasyMutex::new(1);
+        let _handle = m.on_change(|_, _| {});
+        assert_eq!(*m.get_mut().unwrap(), 1);
+
+        let watcher = m.subscribe();
+        let async_watcher = m.subscribe_async();
+        assert_eq!(m.weak_count(), 0);
+        assert!(m.get_mut().is_none());
+        let weak = m.downgrade();
+        assert_eq!(m.weak_count(), 1);
+
+        drop((watcher, async_watcher, weak));
+        assert_eq!(m.weak_count(), 0);
+        assert!(m.get_mut().is_some());
+    }
+
+    #[test]
+    fn debug_does_not_block() {
+        let m = EasyMutex::new(5);
+        assert_eq!(
+            format!("{m:?}"),
+            "EasyMutex { data: 5, poisoned: false, policy: Error, handles: 1 }"
+        );
+        m.with(|_| assert!(format!("{m:?}").contains("data: <locked>")));
+
+        let m = EasyMutex::named("answer", 42);
+        assert!(format!("{m:?}").starts_with("EasyMutex { name: \"answer\", data: 42,"));
+    }
+
+    #[test]
+    fn replace_and_take_return_previous_value() {
+        let m = EasyMutex::new(String::from("a"));
+        assert_eq!(m.replace(String::from("b")), "a");
+        assert_eq!(m.replace_result(String::from("c")), Ok(String::from("b")));
+        assert_eq!(m.take(), "c");
+        assert_eq!(m.take_result(), Ok(String::new()));
+    }
+
+    #[test]
+    fn swap_exchanges_values_and_ignores_self() {
+        let a = EasyMutex::new(1);
+        let b = EasyMutex::new(2);
+        a.swap(&b);
+        assert_eq!((a.read(), b.read()), (2, 1));
+
+        a.swap(&a.clone());
+        assert_eq!(a.read(), 2);
+
+        poison(&b);
+        assert_eq!(a.swap_result(&b), Err(EasyMutexError::Poisoned));
+        assert_eq!(a.read(), 2);
+    }
+
+    #[test]
+    fn failed_multi_lock_does_not_count_as_write() {
+        let (x, y) = (EasyMutex::new(1), EasyMutex::new(2));
+        // Poison the mutex locked second, so the first one is already held when it fails.
+        let (a, b) = if x.addr() < y.addr() { (x, y) } else { (y, x) };
+        poison(&b);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let _observer = a.on_change(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let watcher = a.subscribe();
+
+        assert_eq!(a.swap_result(&b), Err(EasyMutexError::Poisoned));
+        assert_eq!(
+            with_all_result((&a, &b), |(x, y)| mem::swap(x, y)),
+            Err(EasyMutexError::Poisoned)
+        );
+        assert_eq!(a.version(), 0);
+        assert_eq!(watcher.has_changed(), Ok(false));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn write_during_unrelated_unwind_counts() {
+        struct WriteOnDrop(EasyMutex<i32>);
+
+        impl Drop for WriteOnDrop {
+            fn drop(&mut self) {
+                self.0.write(5);
+            }
+        }
+
+        let m = EasyMutex::new(0);
+        let watcher = m.subscribe();
+        let writer = WriteOnDrop(m.clone());
+        let _ = panic::catch_unwind(AssertUnwindSafe(move || {
+            let _writer = writer;
+            panic!("unrelated");
+        }));
+
+        assert!(!m.is_poisoned());
+        assert_eq!(m.read_versioned(), (5, 1));
+        assert_eq!(watcher.has_changed(), Ok(true));
+    }
+
+    #[test]
+    fn opposite_swaps_do_not_deadlock() {
+        let a = EasyMutex::new(0);
+        let b = EasyMutex::new(1);
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let (a, b) = (a.clone(), b.clone());
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        if i % 2 == 0 { a.swap(&b) } else { b.swap(&a) }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!((a.read(), b.read()), (0, 1));
+    }
+
+    #[test]
+    fn conditional_writes() {
+        let m = EasyMutex::new(1);
+        assert_eq!(m.compare_and_set(&1, 2), Ok(()));
+        assert_eq!(m.compare_and_set(&1, 3), Err(2));
+
+        assert_eq!(m.write_if(|v| *v % 2 == 0, 4), Ok(()));
+        assert_eq!(m.write_if(|v| *v > 10, 5), Err(4));
+
+        assert_eq!(m.update_if(|v| *v == 4, |v| std::mem::replace(v, 6)), Ok(4));
+        assert_eq!(
+            m.update_if(|v| *v == 4, |v| std::mem::replace(v, 7)),
+            Err(6)
+        );
+        assert_eq!(m.read(), 6);
+    }
+
+    #[test]
+    fn conditional_writes_report_poison() {
+        let m = EasyMutex::new(1);
+        assert_eq!(m.compare_and_set_result(&1, 2), Ok(Ok(())));
+        assert_eq!(m.write_if_result(|v| *v > 10, 3), Ok(Err(2)));
+        assert_eq!(m.update_if_result(|v| *v == 2, |v| *v * 10), Ok(Ok(20)));
+
+        poison(&m);
+        assert_eq!(
+            m.compare_and_set_result(&2, 3),
+            Err(EasyMutexError::Poisoned)
+        );
+        assert_eq!(
+            m.write_if_result(|_| true, 3),
+            Err(EasyMutexError::Poisoned)
+        );
+        assert_eq!(
+            m.update_if_result(|_| true, |_| ()),
+            Err(EasyMutexError::Poisoned)
+        );
+    }
+
+    #[test]
+    fn compare_and_set_has_a_single_winner() {
+        let m = EasyMutex::new(0);
+        let winners: usize = (0..8)
+            .map(|i| {
+                let m = m.clone();
+                thread::spawn(move || m.compare_and_set(&0, i + 1).is_ok())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap() as usize)
+            .sum();
+        assert_eq!(winners, 1);
+        assert_ne!(m.read(), 0);
+    }
+
+    #[test]
+    fn every_write_bumps_version() {
+        let mut m = EasyMutex::new(0);
+        assert_eq!(m.version(), 0);
+
+        m.write(1);
+        m.update(|v| *v += 1);
+        m.replace(3);
+        assert_eq!(m.try_update(|v| *v += 1), Ok(()));
+        assert_eq!(m.version(), 4);
+
+        m.read();
+        m.with(|_| ());
+        assert_eq!(m.update_if(|v| *v > 10, |v| *v = 0), Err(4));
+        assert_eq!(m.compare_and_set(&0, 5), Err(4));
+        assert_eq!(m.version(), 4);
+
+        *m.get_mut().unwrap() = 5;
+        m.swap(&EasyMutex::new(6));
+        assert_eq!(m.read_versioned(), (6, 6));
+    }
+
+    #[test]
+    fn write_if_version_detects_concurrent_writes() {
+        let m = EasyMutex::new("draft");
+        let (_, version) = m.read_versioned();
+        let other = m.clone();
+        thread::spawn(move || other.write("edited")).join().unwrap();
+
+        assert_eq!(m.write_if_version(version, "stale"), Err(version + 1));
+        assert_eq!(m.read(), "edited");
+        assert_eq!(m.write_if_version(version + 1, "fresh"), Ok(version + 2));
+        assert_eq!(m.read_versioned_result(), Ok(("fresh", version + 2)));
+
+        poison(&m);
+        assert_eq!(
+            m.write_if_version_result(version + 2, "lost"),
+            Err(EasyMutexError::Poisoned)
+        );
+    }
+
+    #[test]
+    fn wait_until_wakes_on_write() {
+        let m = EasyMutex::new(0);
+        let waiter = m.clone();
+        let handle = thread::spawn(move || waiter.wait_until(|v| *v >= 3));
+
+        for _ in 0..3 {
+            thread::sleep(Duration::from_millis(5));
+            m.update(|v| *v += 1);
+        }
+        assert_eq!(handle.join().unwrap(), 3);
+        assert_eq!(m.wait_until_result(|v| *v == 3), Ok(3));
+    }
+
+    #[test]
+    fn waiters_are_only_counted_while_waiting() {
+        let m = EasyMutex::new(0);
+        let waiter = m.clone();
+        let handle =
+            thread::spawn(move || waiter.wait_until_timeout(|v| *v > 0, Duration::from_secs(10)));
+        while m.0.waiters.load(Ordering::Relaxed) == 0 {
+            thread::yield_now();
+        }
+
+        m.write(1);
+        assert_eq!(handle.join().unwrap(), Ok(1));
+        assert_eq!(m.0.waiters.load(Ordering::Relaxed), 0);
+        assert_eq!(
+            m.wait_until_timeout(|v| *v > 1, Duration::from_millis(5)),
+            Err(EasyMutexError::TimedOut)
+        );
+        assert_eq!(m.0.waiters.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn wait_until_timeout_accepts_unbounded_timeouts() {
+        let m = EasyMutex::new(0);
+        let writer = m.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(5));
+            writer.write(7);
+        });
+        assert_eq!(m.wait_until_timeout(|v| *v > 0, Duration::MAX), Ok(7));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn wait_until_timeout_gives_up() {
+        let m = EasyMutex::new(0);
+        let timeout = Duration::from_millis(20);
+        let start = Instant::now();
+        assert_eq!(
+            m.wait_until_timeout(|v| *v > 0, timeout),
+            Err(EasyMutexError::TimedOut)
+        );
+        assert!(start.elapsed() >= timeout);
+
+        let writer = m.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(5));
+            writer.write(7);
+        });
+        assert_eq!(
+            m.wait_until_timeout(|v| *v > 0, Duration::from_secs(10)),
+            Ok(7)
+        );
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn wait_for_change_returns_new_version() {
+        let m = EasyMutex::new("a");
+        let (_, since) = m.read_versioned();
+        let writer = m.clone();
+        let handle = thread::spawn(move || writer.write("b"));
+
+        let (value, version) = m.wait_for_change(since);
+        handle.join().unwrap();
+        assert_eq!((value, version), ("b", since + 1));
+
+        poison(&m);
+        assert_eq!(
+            m.wait_for_change_result(version),
+            Err(EasyMutexError::Poisoned)
+        );
+    }
+
     #[test]
     fn clone_mutex_and_share() {
         let m = EasyMutex::new(0);
@@ -159,6 +2003,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::manual_range_contains)]
     fn concurrent_modify() {
         let m = EasyMutex::new(0);
         let mut handles = vec![];
@@ -179,4 +2024,25 @@ mod tests {
         let final_val = m.read();
         assert!(final_val >= 10000 && final_val <= 100000000);
     }
+
+    #[test]
+    fn concurrent_update_is_atomic() {
+        let m = EasyMutex::new(0);
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let m_clone = m.clone();
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        m_clone.update(|v| *v += 1);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(m.read(), 8000);
+        assert_eq!(m.update_result(|v| *v * 2).unwrap(), 16000);
+    }
 }
@@ -0,0 +1,288 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use crate::{EasyMutex, EasyMutexError};
+
+/// A tuple of `&EasyMutex` handles that can be locked together by [`with_all`].
+///
+/// Implemented for tuples of one to eight handles, which may wrap different types. `F` is the
+/// closure receiving a tuple with a mutable reference to each value, and `R` its output.
+pub trait LockAll<F, R> {
+    /// Locks every handle in address order, runs `f` and releases every lock.
+    fn lock_all(self, f: F) -> Result<R, EasyMutexError>;
+}
+
+/// Locks several mutexes at once and runs `f` with a mutable reference to each value.
+///
+/// The locks are always acquired in the same global order (by address, as
+/// [`EasyMutex::swap`] does), whatever the order of the tuple, so two threads locking the same
+/// mutexes in different orders cannot deadlock. Every lock is released before returning, and
+/// every mutex counts as written for [`EasyMutex::version`].
+///
+/// # Panics
+///
+/// Panics if the same mutex (or two clones of it) is passed more than once, since it cannot
+/// hand out two mutable references to the same value. Also panics if any mutex is poisoned,
+/// unless its [`PoisonPolicy`](crate::PoisonPolicy) recovers from it.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyMutex, with_all};
+///
+/// let checking = EasyMutex::new(100);
+/// let savings = EasyMutex::new(0);
+/// let log = EasyMutex::new(Vec::new());
+///
+/// with_all((&checking, &savings, &log), |(from, to, log)| {
+///     *from -= 30;
+///     *to += 30;
+///     log.push("moved 30 to savings");
+/// });
+/// assert_eq!((checking.read(), savings.read()), (70, 30));
+/// ```
+pub fn with_all<H, F, R>(handles: H, f: F) -> R
+where
+    H: LockAll<F, R>,
+{
+    match with_all_result(handles, f) {
+        Err(EasyMutexError::Duplicate) => {
+            panic!("the same EasyMutex was passed more than once to with_all")
+        }
+        result => result.unwrap(),
+    }
+}
+
+/// Same as [`with_all`], but return a `Result<R, EasyMutexError>` type.
+///
+/// If a mutex cannot be locked, the locks already acquired are released and `f` is not called.
+/// Passing the same mutex (or two clones of it) more than once returns
+/// `Err(EasyMutexError::Duplicate)` without locking anything.
+///
+/// # Panics
+///
+/// Panics on poison if a mutex has [`PoisonPolicy::Panic`](crate::PoisonPolicy::Panic).
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyMutex, EasyMutexError, with_all_result};
+///
+/// fn transfer(from: &EasyMutex<u32>, to: &EasyMutex<u32>, n: u32) -> Result<(), EasyMutexError> {
+///     with_all_result((from, to), |(from, to)| {
+///         *from -= n;
+///         *to += n;
+///     })
+/// }
+///
+/// let account = EasyMutex::new(100);
+/// assert_eq!(transfer(&account, &account, 10), Err(EasyMutexError::Duplicate));
+/// assert_eq!(account.read(), 100);
+/// ```
+pub fn with_all_result<H, F, R>(handles: H, f: F) -> Result<R, EasyMutexError>
+where
+    H: LockAll<F, R>,
+{
+    handles.lock_all(f)
+}
+
+/// Locks several mutexes at once, with closure-like syntax.
+///
+/// `lock_all!(a, b => |x, y| body)` is shorthand for
+/// `with_all((&a, &b), |(x, y)| body)`; see [`with_all`] for the locking order and panics.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyMutex, lock_all};
+///
+/// let inbox = EasyMutex::new(vec!["mail"]);
+/// let archive = EasyMutex::new(Vec::new());
+///
+/// lock_all!(inbox, archive => |inbox, archive| archive.extend(inbox.drain(..)));
+/// assert_eq!(archive.read(), vec!["mail"]);
+/// ```
+#[macro_export]
+macro_rules! lock_all {
+    ($($mutex:expr),+ $(,)? => |$($value:pat_param),+| $body:expr) => {
+        $crate::with_all(($(&$mutex,)+), |($($value,)+)| $body)
+    };
+}
+
+/// Returns the indices of `addrs` sorted by address, or an error on duplicates.
+fn lock_order<const N: usize>(addrs: [usize; N]) -> Result<[usize; N], EasyMutexError> {
+    let mut order: [usize; N] = std::array::from_fn(|i| i);
+    order.sort_unstable_by_key(|&i| addrs[i]);
+    if order
+        .windows(2)
+        .any(|pair| addrs[pair[0]] == addrs[pair[1]])
+    {
+        return Err(EasyMutexError::Duplicate);
+    }
+    Ok(order)
+}
+
+macro_rules! impl_lock_all {
+    ($(($idx:tt, $T:ident, $guard:ident)),+) => {
+        impl<'m, F, R, $($T),+> LockAll<F, R> for ($(&'m EasyMutex<$T>,)+)
+        where
+            F: FnOnce(($(&mut $T,)+)) -> R,
+        {
+            fn lock_all(self, f: F) -> Result<R, EasyMutexError> {
+                $(let mut $guard = None;)+
+                for i in lock_order([$(self.$idx.addr()),+])? {
+                    match i {
+                        $($idx => $guard = Some(self.$idx.lock()?),)+
+                        _ => unreachable!(),
+                    }
+                }
+                // Nothing counts as written until every lock is acquired.
+                $(let mut $guard = self.$idx.writing($guard.unwrap());)+
+                let result = f(($(&mut *$guard,)+));
+                // Release every lock before running the observers of any mutex.
+                let _notify = ($($guard.release(),)+);
+                Ok(result)
+            }
+        }
+    };
+}
+
+impl_lock_all!((0, A, a));
+impl_lock_all!((0, A, a), (1, B, b));
+impl_lock_all!((0, A, a), (1, B, b), (2, C, c));
+impl_lock_all!((0, A, a), (1, B, b), (2, C, c), (3, D, d));
+impl_lock_all!((0, A, a), (1, B, b), (2, C, c), (3, D, d), (4, E, e));
+impl_lock_all!(
+    (0, A, a),
+    (1, B, b),
+    (2, C, c),
+    (3, D, d),
+    (4, E, e),
+    (5, G, g)
+);
+impl_lock_all!(
+    (0, A, a),
+    (1, B, b),
+    (2, C, c),
+    (3, D, d),
+    (4, E, e),
+    (5, G, g),
+    (6, H, h)
+);
+impl_lock_all!(
+    (0, A, a),
+    (1, B, b),
+    (2, C, c),
+    (3, D, d),
+    (4, E, e),
+    (5, G, g),
+    (6, H, h),
+    (7, I, i)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{with_all, with_all_result};
+    use crate::{EasyMutex, EasyMutexError};
+    use std::thread;
+
+    #[test]
+    fn opposite_transfers_do_not_deadlock() {
+        let a = EasyMutex::new(1000);
+        let b = EasyMutex::new(1000);
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let (a, b) = (a.clone(), b.clone());
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        if i % 2 == 0 {
+                            with_all((&a, &b), |(from, to)| {
+                                *from -= 1;
+                                *to += 1;
+                            });
+                        } else {
+                            lock_all!(b, a => |from, to| {
+                                *from -= 1;
+                                *to += 1;
+                            });
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(a.read() + b.read(), 2000);
+    }
+
+    #[test]
+    fn mixed_types_and_arity() {
+        let name = EasyMutex::new(String::from("queue"));
+        let items = EasyMutex::new(vec![1, 2, 3]);
+        let moved = EasyMutex::new(0usize);
+        let target = EasyMutex::new(Vec::new());
+
+        let count = with_all(
+            (&name, &items, &moved, &target),
+            |(name, items, moved, target)| {
+                name.push_str("-drained");
+                target.append(items);
+                *moved = target.len();
+                *moved
+            },
+        );
+        assert_eq!(count, 3);
+        assert_eq!(name.read(), "queue-drained");
+        assert!(items.read().is_empty());
+
+        assert_eq!(with_all((&moved,), |(moved,)| *moved), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "more than once")]
+    fn same_handle_twice_panics() {
+        let a = EasyMutex::new(1);
+        let alias = a.clone();
+        with_all((&a, &alias), |(x, y)| *x + *y);
+    }
+
+    #[test]
+    fn same_handle_twice_is_an_error() {
+        let a = EasyMutex::new(1);
+        let b = EasyMutex::new(2);
+        assert_eq!(
+            with_all_result((&a, &b, &a.clone()), |(x, y, z)| *x + *y + *z),
+            Err(EasyMutexError::Duplicate)
+        );
+        assert_eq!((a.version(), a.try_read()), (0, Ok(1)));
+    }
+
+    #[test]
+    fn poisoned_member_releases_other_locks() {
+        let a = EasyMutex::new(1);
+        let b = EasyMutex::new(2);
+        let poisoner = b.clone();
+        let _ = thread::spawn(move || poisoner.update(|_| panic!("poison"))).join();
+
+        assert_eq!(
+            with_all_result((&a, &b), |(x, y)| *x + *y),
+            Err(EasyMutexError::Poisoned)
+        );
+        assert_eq!(a.try_read(), Ok(1));
+    }
+}
@@ -0,0 +1,250 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use crate::watch::Wakers;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+
+type Callback<T> = dyn Fn(&T, &T) + Send + Sync;
+
+/// The callbacks registered with [`EasyMutex::on_change`](crate::EasyMutex::on_change).
+pub(crate) struct Observers<T> {
+    /// Number of registered callbacks, so that writes skip the list while nobody observes.
+    len: AtomicUsize,
+    list: Mutex<List<T>>,
+}
+
+struct List<T> {
+    next_id: u64,
+    /// Set by the first registration, which is the only place where `T: Clone` is known.
+    clone: Option<fn(&T) -> T>,
+    callbacks: Vec<(u64, Arc<Callback<T>>)>,
+}
+
+/// The callbacks to run for one write, with snapshots of the value before and after it, and
+/// the async watchers to wake.
+///
+/// They run when this is dropped, which the write path does only after releasing its locks.
+pub(crate) struct Notify<T> {
+    change: Option<Change<T>>,
+    /// Woken after the callbacks ran.
+    wakers: Wakers,
+}
+
+struct Change<T> {
+    old: T,
+    new: T,
+    callbacks: Vec<Arc<Callback<T>>>,
+}
+
+/// Keeps a callback registered with [`EasyMutex::on_change`](crate::EasyMutex::on_change)
+/// alive; dropping it unregisters the callback.
+#[must_use = "dropping the handle unregisters the callback right away"]
+pub struct ObserverHandle<T> {
+    /// Points to the callbacks rather than to the mutex, so that it does not count as a weak
+    /// reference to it.
+    observers: Weak<Observers<T>>,
+    id: u64,
+}
+
+impl<T> Observers<T> {
+    fn list(&self) -> MutexGuard<'_, List<T>> {
+        // Callbacks never run under this lock, so it cannot be poisoned in a meaningful way.
+        self.list.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Snapshots the value before a write, or returns `None` if nobody is observing it.
+    pub(crate) fn before(&self, value: &T) -> Option<T> {
+        if self.len.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+        let list = self.list();
+        match list.clone {
+            Some(clone) if !list.callbacks.is_empty() => Some(clone(value)),
+            _ => None,
+        }
+    }
+
+    /// Snapshots the value after a write and collects the callbacks to notify.
+    pub(crate) fn after(&self, old: Option<T>, new: &T) -> Notify<T> {
+        let Some(old) = old else {
+            return Notify::none();
+        };
+        let list = self.list();
+        match list.clone {
+            Some(clone) if !list.callbacks.is_empty() => Notify {
+                change: Some(Change {
+                    old,
+                    new: clone(new),
+                    callbacks: list.callbacks.iter().map(|(_, f)| f.clone()).collect(),
+                }),
+                wakers: Wakers::default(),
+            },
+            _ => Notify::none(),
+        }
+    }
+
+    /// Registers `callback` and returns its id.
+    pub(crate) fn register(&self, clone: fn(&T) -> T, callback: Arc<Callback<T>>) -> u64 {
+        let mut list = self.list();
+        let id = list.next_id;
+        list.next_id += 1;
+        list.clone = Some(clone);
+        list.callbacks.push((id, callback));
+        self.len.store(list.callbacks.len(), Ordering::Relaxed);
+        id
+    }
+
+    fn unregister(&self, id: u64) {
+        let mut list = self.list();
+        list.callbacks.retain(|&(other, _)| other != id);
+        self.len.store(list.callbacks.len(), Ordering::Relaxed);
+    }
+}
+
+impl<T> Default for Observers<T> {
+    fn default() -> Self {
+        Self {
+            len: AtomicUsize::new(0),
+            list: Mutex::new(List {
+                next_id: 0,
+                clone: None,
+                callbacks: Vec::new(),
+            }),
+        }
+    }
+}
+
+impl<T> Notify<T> {
+    /// Nothing to notify.
+    pub(crate) fn none() -> Self {
+        Self {
+            change: None,
+            wakers: Wakers::default(),
+        }
+    }
+
+    /// Also wakes `wakers` once the callbacks ran.
+    pub(crate) fn waking(mut self, wakers: Wakers) -> Self {
+        self.wakers = wakers;
+        self
+    }
+}
+
+impl<T> Drop for Notify<T> {
+    fn drop(&mut self) {
+        if let Some(change) = self.change.take() {
+            for callback in change.callbacks {
+                callback(&change.old, &change.new);
+            }
+        }
+    }
+}
+
+impl<T> ObserverHandle<T> {
+    pub(crate) fn new(observers: &Arc<Observers<T>>, id: u64) -> Self {
+        Self {
+            observers: Arc::downgrade(observers),
+            id,
+        }
+    }
+}
+
+impl<T> Drop for ObserverHandle<T> {
+    fn drop(&mut self) {
+        if let Some(observers) = self.observers.upgrade() {
+            observers.unregister(self.id);
+        }
+    }
+}
+
+impl<T> fmt::Debug for ObserverHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObserverHandle")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::EasyMutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Counts how many times it was cloned.
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Self(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn callbacks_receive_old_and_new_values() {
+        let m = EasyMutex::new(1);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = log.clone();
+        let handle = m.on_change(move |old, new| sink.lock().unwrap().push((*old, *new)));
+
+        m.write(2);
+        m.update(|v| *v *= 10);
+        assert_eq!(m.replace(7), 20);
+        assert_eq!(m.compare_and_set(&0, 8), Err(7));
+        assert_eq!(*log.lock().unwrap(), [(1, 2), (2, 20), (20, 7)]);
+
+        drop(handle);
+        m.write(9);
+        assert_eq!(log.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn values_are_only_snapshot_while_observed() {
+        let clones = Arc::new(AtomicUsize::new(0));
+        let m = EasyMutex::new(Counted(clones.clone()));
+        m.update(|_| {});
+        assert_eq!(clones.load(Ordering::SeqCst), 0);
+
+        let handle = m.on_change(|_, _| {});
+        m.update(|_| {});
+        assert_eq!(clones.load(Ordering::SeqCst), 2);
+
+        drop(handle);
+        m.update(|_| {});
+        assert_eq!(clones.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn callbacks_run_after_every_lock_is_released() {
+        let a = EasyMutex::new(1);
+        let b = EasyMutex::new(2);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        // Each callback locks the other mutex, which would deadlock under `swap`'s locks.
+        let (other, sink) = (b.downgrade(), seen.clone());
+        let _on_a = a.on_change(move |_, new| sink.lock().unwrap().push((*new, other.read())));
+        let (other, sink) = (a.downgrade(), seen.clone());
+        let _on_b = b.on_change(move |_, new| sink.lock().unwrap().push((*new, other.read())));
+
+        a.swap(&b);
+        let mut seen = seen.lock().unwrap().clone();
+        seen.sort();
+        assert_eq!(seen, [(1, Some(2)), (2, Some(1))]);
+    }
+}
@@ -0,0 +1,77 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use crate::EasyMutexError;
+use std::sync::PoisonError;
+
+/// What an `EasyMutex` does when it finds its lock poisoned.
+///
+/// A mutex is poisoned when a thread panics while holding its lock. The policy is chosen at
+/// construction time with [`EasyMutex::new_with_policy`](crate::EasyMutex::new_with_policy)
+/// and is shared by every clone of the handle.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyMutex, PoisonPolicy};
+///
+/// let shared = EasyMutex::new_with_policy(1, PoisonPolicy::Recover);
+/// let poisoner = shared.clone();
+/// let _ = std::thread::spawn(move || poisoner.update(|_| panic!("boom"))).join();
+///
+/// // The panicking thread left the value untouched, so it is safe to keep using it.
+/// assert_eq!(shared.read(), 1);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PoisonPolicy {
+    /// Every method panics on poison, including the `_result` ones.
+    Panic,
+    /// The `_result`, `try_` and `_timeout` methods return
+    /// [`EasyMutexError::Poisoned`](crate::EasyMutexError::Poisoned), while the plain methods,
+    /// which cannot report errors, panic. This is the default.
+    #[default]
+    Error,
+    /// Poison is ignored and the data is used as the panicking thread left it.
+    /// The mutex stays poisoned.
+    Recover,
+    /// Same as [`PoisonPolicy::Recover`], but the poison flag is also cleared on the first
+    /// acquisition that observes it.
+    RecoverAndClear,
+}
+
+impl PoisonPolicy {
+    /// Applies the policy to a poisoned acquisition, calling `clear_poison` for
+    /// [`PoisonPolicy::RecoverAndClear`].
+    pub(crate) fn apply<G>(
+        self,
+        err: PoisonError<G>,
+        clear_poison: impl FnOnce(),
+    ) -> Result<G, EasyMutexError> {
+        match self {
+            PoisonPolicy::Panic => {
+                // Release the lock before unwinding so the panic is not reported as a new poisoning.
+                drop(err);
+                panic!("{}", EasyMutexError::Poisoned);
+            }
+            PoisonPolicy::Error => Err(EasyMutexError::Poisoned),
+            PoisonPolicy::Recover => Ok(err.into_inner()),
+            PoisonPolicy::RecoverAndClear => {
+                clear_poison();
+                Ok(err.into_inner())
+            }
+        }
+    }
+}
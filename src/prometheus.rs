@@ -0,0 +1,266 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+//! Prometheus text exposition of the registry, enabled by the `prometheus` feature.
+
+use crate::registry::registered_and_retired;
+use crate::{LATENCY_BUCKETS, LockStats};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Reads a counter or gauge from a [`Series`].
+type Scalar = fn(&Series) -> u64;
+
+/// The metrics of every mutex sharing a name, which Prometheus sees as a single series.
+#[derive(Default)]
+struct Series {
+    handles: usize,
+    poisoned: bool,
+    stats: LockStats,
+}
+
+impl Series {
+    fn add(&mut self, handles: usize, poisoned: bool, stats: &LockStats) {
+        self.handles += handles;
+        self.poisoned |= poisoned;
+        self.stats.add(stats);
+    }
+}
+
+/// Renders the metrics of every named mutex in the Prometheus text exposition format.
+///
+/// Each metric is labelled by the mutex name; mutexes sharing a name are added together.
+/// Counters and histograms also include the metrics of the dropped mutexes and those cleared
+/// by [`EasyMutex::reset_stats`](crate::EasyMutex::reset_stats), so they never go down.
+/// The output, served as `text/plain; version=0.0.4`, contains:
+///
+/// * `easy_mutex_acquisitions_total`, `easy_mutex_contended_total` and
+///   `easy_mutex_poison_events_total` counters,
+/// * `easy_mutex_handles` and `easy_mutex_poisoned` gauges,
+/// * `easy_mutex_wait_seconds` and `easy_mutex_hold_seconds` histograms, bucketed by
+///   [`LATENCY_BUCKETS`]. The hold histogram counts hold periods, so its count can exceed
+///   the acquisitions (see [`LockStats::hold_buckets`]).
+///
+/// Requires the `prometheus` feature.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyMutex, render_prometheus};
+///
+/// let cache = EasyMutex::named("cache", 0);
+/// cache.write(1);
+///
+/// let text = render_prometheus();
+/// assert!(text.contains("easy_mutex_acquisitions_total{name=\"cache\"} 1\n"));
+/// assert!(text.contains("easy_mutex_hold_seconds_count{name=\"cache\"} 1\n"));
+/// ```
+pub fn render_prometheus() -> String {
+    let mut series: BTreeMap<String, Series> = BTreeMap::new();
+    let (infos, retired) = registered_and_retired();
+    for info in infos {
+        series
+            .entry(info.name)
+            .or_default()
+            .add(info.handles, info.poisoned, &info.stats);
+    }
+    for (name, stats) in retired {
+        series.entry(name).or_default().add(0, false, &stats);
+    }
+    let labels: Vec<_> = series
+        .iter()
+        .map(|(name, series)| (format!("name=\"{}\"", escape(name)), series))
+        .collect();
+
+    let mut out = String::new();
+    let scalars: [(&str, &str, &str, Scalar); 5] = [
+        (
+            "easy_mutex_acquisitions_total",
+            "Number of times the lock was acquired.",
+            "counter",
+            |s| s.stats.acquisitions,
+        ),
+        (
+            "easy_mutex_contended_total",
+            "Acquisitions that had to wait for the lock.",
+            "counter",
+            |s| s.stats.contended,
+        ),
+        (
+            "easy_mutex_poison_events_total",
+            "Acquisitions that found the lock poisoned.",
+            "counter",
+            |s| s.stats.poison_events,
+        ),
+        (
+            "easy_mutex_handles",
+            "Number of handles alive.",
+            "gauge",
+            |s| s.handles as u64,
+        ),
+        (
+            "easy_mutex_poisoned",
+            "Whether the lock is poisoned.",
+            "gauge",
+            |s| s.poisoned.into(),
+        ),
+    ];
+    for (metric, help, kind, value) in scalars {
+        header(&mut out, metric, help, kind);
+        for (label, series) in &labels {
+            let _ = writeln!(out, "{metric}{{{label}}} {}", value(series));
+        }
+    }
+
+    histogram(
+        &mut out,
+        "easy_mutex_wait_seconds",
+        "Time spent waiting for the lock.",
+        &labels,
+        |s| (&s.stats.wait_buckets, s.stats.total_wait),
+    );
+    histogram(
+        &mut out,
+        "easy_mutex_hold_seconds",
+        "Time the lock was held, per hold period.",
+        &labels,
+        |s| (&s.stats.hold_buckets, s.stats.total_hold),
+    );
+    out
+}
+
+fn header(out: &mut String, metric: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {metric} {help}");
+    let _ = writeln!(out, "# TYPE {metric} {kind}");
+}
+
+fn histogram(
+    out: &mut String,
+    metric: &str,
+    help: &str,
+    labels: &[(String, &Series)],
+    data: impl Fn(&Series) -> (&[u64], Duration),
+) {
+    header(out, metric, help, "histogram");
+    for (label, series) in labels {
+        let (buckets, sum) = data(series);
+        let mut count = 0;
+        for (bound, n) in LATENCY_BUCKETS.iter().zip(buckets) {
+            count += n;
+            let le = bound.as_secs_f64();
+            let _ = writeln!(out, "{metric}_bucket{{{label},le=\"{le}\"}} {count}");
+        }
+        count += buckets[LATENCY_BUCKETS.len()];
+        let _ = writeln!(out, "{metric}_bucket{{{label},le=\"+Inf\"}} {count}");
+        let _ = writeln!(out, "{metric}_sum{{{label}}} {}", sum.as_secs_f64());
+        let _ = writeln!(out, "{metric}_count{{{label}}} {count}");
+    }
+}
+
+/// Escapes a label value as required by the text format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_prometheus;
+    use crate::EasyMutex;
+
+    fn lines_for(text: &str, label: &str) -> Vec<String> {
+        text.lines()
+            .filter(|line| line.contains(label))
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn renders_counters_and_histograms() {
+        let m = EasyMutex::named("prometheus-test", 0);
+        let same_name = EasyMutex::named("prometheus-test", 0);
+        m.write(1);
+        same_name.write(1);
+        let _clone = m.clone();
+
+        let text = render_prometheus();
+        assert!(text.contains("# TYPE easy_mutex_wait_seconds histogram\n"));
+        let lines = lines_for(&text, "name=\"prometheus-test\"");
+        for expected in [
+            "easy_mutex_acquisitions_total{name=\"prometheus-test\"} 2",
+            "easy_mutex_contended_total{name=\"prometheus-test\"} 0",
+            "easy_mutex_handles{name=\"prometheus-test\"} 3",
+            "easy_mutex_poisoned{name=\"prometheus-test\"} 0",
+            "easy_mutex_wait_seconds_bucket{name=\"prometheus-test\",le=\"0.000001\"} 2",
+            "easy_mutex_wait_seconds_bucket{name=\"prometheus-test\",le=\"+Inf\"} 2",
+            "easy_mutex_wait_seconds_sum{name=\"prometheus-test\"} 0",
+            "easy_mutex_hold_seconds_count{name=\"prometheus-test\"} 2",
+        ] {
+            assert!(
+                lines.iter().any(|line| line == expected),
+                "missing {expected}"
+            );
+        }
+        assert_eq!(lines.len(), 5 + 2 * 11);
+    }
+
+    #[test]
+    fn counters_keep_dropped_and_reset_mutexes() {
+        let acquisitions = || {
+            let text = render_prometheus();
+            lines_for(
+                &text,
+                "easy_mutex_acquisitions_total{name=\"prometheus-retired\"}",
+            )
+            .concat()
+        };
+        let m = EasyMutex::named("prometheus-retired", 0);
+        let dropped = EasyMutex::named("prometheus-retired", 0);
+        m.write(1);
+        dropped.write(1);
+        dropped.write(2);
+        assert_eq!(
+            acquisitions(),
+            "easy_mutex_acquisitions_total{name=\"prometheus-retired\"} 3"
+        );
+
+        drop(dropped);
+        assert_eq!(
+            acquisitions(),
+            "easy_mutex_acquisitions_total{name=\"prometheus-retired\"} 3"
+        );
+
+        m.reset_stats();
+        assert_eq!(m.stats().acquisitions, 0);
+        m.write(2);
+        assert_eq!(
+            acquisitions(),
+            "easy_mutex_acquisitions_total{name=\"prometheus-retired\"} 4"
+        );
+        let text = render_prometheus();
+        assert!(text.contains("easy_mutex_handles{name=\"prometheus-retired\"} 1\n"));
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        let _m = EasyMutex::named("a \"quoted\"\\name\n", ());
+        let text = render_prometheus();
+        assert!(text.contains("easy_mutex_handles{name=\"a \\\"quoted\\\"\\\\name\\n\"} 1\n"));
+    }
+}
@@ -0,0 +1,310 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+//! Global registry of the named mutexes, enabled by the `registry` feature. Without it, the
+//! links to the registry are zero-sized and every call compiles to nothing.
+//!
+//! The registry does not point to the shared state of the mutexes, which would count as a
+//! weak reference to it and keep [`EasyMutex::get_mut`](crate::EasyMutex::get_mut) from
+//! working. Each named mutex and each of its handles share an [`Entry`] instead.
+
+#[cfg(feature = "registry")]
+use crate::LockStats;
+use crate::stats::Recorder;
+use std::sync::Arc;
+
+#[cfg(feature = "registry")]
+use std::collections::BTreeMap;
+#[cfg(feature = "registry")]
+use std::mem;
+#[cfg(feature = "registry")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "registry")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "registry")]
+use std::thread;
+
+#[cfg(feature = "registry")]
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    entries: Vec::new(),
+    retired: BTreeMap::new(),
+});
+
+#[cfg(feature = "registry")]
+struct Registry {
+    /// Every named mutex, including the dropped ones that were not pruned yet.
+    entries: Vec<Arc<Entry>>,
+    /// Metrics no live mutex accounts for anymore, by name: those of the dropped mutexes and
+    /// those cleared by [`EasyMutex::reset_stats`](crate::EasyMutex::reset_stats). Exported
+    /// counters add them, so that they never go down.
+    retired: BTreeMap<String, LockStats>,
+}
+
+/// A snapshot of a named [`EasyMutex`](crate::EasyMutex), returned by [`registered_mutexes`].
+#[cfg(feature = "registry")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LockInfo {
+    /// The name given to [`EasyMutex::named`](crate::EasyMutex::named).
+    pub name: String,
+    /// The type of the value, as given by [`std::any::type_name`].
+    pub type_name: &'static str,
+    /// Number of `EasyMutex` handles alive.
+    pub handles: usize,
+    /// Whether the lock is poisoned.
+    pub poisoned: bool,
+    /// The lock metrics, as given by [`EasyMutex::stats`](crate::EasyMutex::stats).
+    pub stats: LockStats,
+}
+
+/// What the registry knows about a named mutex.
+///
+/// Owned by the registry, the shared state of the mutex and every handle to it, so that the
+/// handles can be counted without the registry referencing the shared state.
+#[cfg(feature = "registry")]
+struct Entry {
+    name: String,
+    type_name: &'static str,
+    stats: Arc<Recorder>,
+    /// Mirrors the poison flag of the mutex, see [`PoisonMark`].
+    poisoned: AtomicBool,
+}
+
+/// Links a mutex, or one of its handles, to its entry in the registry; empty if it is not
+/// named.
+#[derive(Clone, Default)]
+pub(crate) struct Registered(#[cfg(feature = "registry")] Option<Arc<Entry>>);
+
+/// Marks the registry entry as poisoned if dropped while panicking, as the guard it goes with
+/// poisons the mutex.
+pub(crate) struct PoisonMark<'a> {
+    #[cfg(feature = "registry")]
+    poisoned: Option<&'a AtomicBool>,
+    #[cfg(not(feature = "registry"))]
+    poisoned: std::marker::PhantomData<&'a ()>,
+}
+
+#[cfg(feature = "registry")]
+impl Registered {
+    /// Adds a named mutex to the registry, pruning the dropped ones.
+    pub(crate) fn new(name: &str, type_name: &'static str, stats: &Arc<Recorder>) -> Self {
+        let entry = Arc::new(Entry {
+            name: name.to_owned(),
+            type_name,
+            stats: Arc::clone(stats),
+            poisoned: AtomicBool::new(false),
+        });
+        let mut registry = Registry::lock();
+        registry.prune();
+        registry.entries.push(Arc::clone(&entry));
+        Self(Some(entry))
+    }
+
+    /// Resets the metrics of the mutex, keeping the values reset in the exported counters.
+    pub(crate) fn reset_stats(&self, stats: &Recorder) {
+        match &self.0 {
+            Some(entry) => {
+                // Under the lock, so that an export sees the metrics either here or retired.
+                let mut registry = Registry::lock();
+                registry.retire(entry);
+                stats.reset();
+            }
+            None => stats.reset(),
+        }
+    }
+
+    /// Starts watching for a panic while the lock is held.
+    pub(crate) fn mark(&self) -> PoisonMark<'_> {
+        // Like `std`, a guard acquired while already panicking does not poison the mutex.
+        let poisoned = self.0.as_ref().filter(|_| !thread::panicking());
+        PoisonMark {
+            poisoned: poisoned.map(|entry| &entry.poisoned),
+        }
+    }
+
+    /// Called after the poison flag of the mutex is cleared.
+    pub(crate) fn clear_poison(&self) {
+        if let Some(entry) = &self.0 {
+            entry.poisoned.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(feature = "registry")]
+impl Registry {
+    fn lock() -> MutexGuard<'static, Self> {
+        REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Removes the dropped mutexes, retiring their metrics.
+    fn prune(&mut self) {
+        let mut entries = mem::take(&mut self.entries);
+        entries.retain(|entry| {
+            let dropped = entry.is_dropped();
+            if dropped {
+                self.retire(entry);
+            }
+            !dropped
+        });
+        self.entries = entries;
+    }
+
+    /// Adds the current metrics of `entry` to the retired ones of its name.
+    fn retire(&mut self, entry: &Entry) {
+        let retired = self.retired.entry(entry.name.clone()).or_default();
+        retired.add(&entry.stats.snapshot());
+    }
+}
+
+#[cfg(feature = "registry")]
+impl Entry {
+    /// Returns `true` once the mutex and all its handles are dropped, leaving only the
+    /// registry. Nothing can start referencing the entry again then.
+    fn is_dropped(self: &Arc<Self>) -> bool {
+        Arc::strong_count(self) == 1
+    }
+
+    fn info(self: &Arc<Self>) -> LockInfo {
+        LockInfo {
+            name: self.name.clone(),
+            type_name: self.type_name,
+            // Not counting the registry and the shared state of the mutex.
+            handles: Arc::strong_count(self) - 2,
+            poisoned: self.poisoned.load(Ordering::Relaxed),
+            stats: self.stats.snapshot(),
+        }
+    }
+}
+
+#[cfg(feature = "registry")]
+impl Drop for PoisonMark<'_> {
+    fn drop(&mut self) {
+        if let Some(poisoned) = self.poisoned {
+            if thread::panicking() {
+                poisoned.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "registry"))]
+impl Registered {
+    pub(crate) fn new(_name: &str, _type_name: &'static str, _stats: &Arc<Recorder>) -> Self {
+        Self()
+    }
+
+    pub(crate) fn mark(&self) -> PoisonMark<'_> {
+        PoisonMark {
+            poisoned: std::marker::PhantomData,
+        }
+    }
+
+    pub(crate) fn clear_poison(&self) {}
+
+    #[cfg(feature = "stats")]
+    pub(crate) fn reset_stats(&self, stats: &Recorder) {
+        stats.reset();
+    }
+}
+
+/// Lists the named mutexes that are still alive, in creation order.
+///
+/// Only mutexes created with [`EasyMutex::named`](crate::EasyMutex::named) are listed, and
+/// the registry does not keep them alive. Never locks the mutexes themselves, so it can be
+/// called to debug a deadlock. Requires the `registry` feature.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyMutex, registered_mutexes};
+///
+/// let sessions = EasyMutex::named("sessions", vec![1, 2]);
+/// let _clone = sessions.clone();
+///
+/// let info = registered_mutexes()
+///     .into_iter()
+///     .find(|info| info.name == "sessions")
+///     .unwrap();
+/// assert!(info.type_name.ends_with("Vec<i32>"));
+/// assert_eq!(info.handles, 2);
+/// assert!(!info.poisoned);
+/// ```
+#[cfg(feature = "registry")]
+pub fn registered_mutexes() -> Vec<LockInfo> {
+    let mut registry = Registry::lock();
+    registry.prune();
+    registry.entries.iter().map(Entry::info).collect()
+}
+
+/// Same as [`registered_mutexes`], also returning the retired metrics by name, taken together
+/// so that none are counted twice or missed.
+#[cfg(feature = "prometheus")]
+pub(crate) fn registered_and_retired() -> (Vec<LockInfo>, BTreeMap<String, LockStats>) {
+    let mut registry = Registry::lock();
+    registry.prune();
+    let infos = registry.entries.iter().map(Entry::info).collect();
+    (infos, registry.retired.clone())
+}
+
+#[cfg(all(test, feature = "registry"))]
+mod tests {
+    use super::registered_mutexes;
+    use crate::EasyMutex;
+    use std::thread;
+
+    fn find(name: &str) -> Option<super::LockInfo> {
+        registered_mutexes()
+            .into_iter()
+            .find(|info| info.name == name)
+    }
+
+    #[test]
+    fn named_mutexes_are_listed_while_alive() {
+        let mut m = EasyMutex::named("registry-test", 0u8);
+        let _anonymous = EasyMutex::new(0u8);
+        m.update(|v| *v += 1);
+
+        let info = find("registry-test").unwrap();
+        assert_eq!(info.type_name, "u8");
+        assert_eq!(info.handles, 1);
+        assert_eq!(info.stats.acquisitions, 1);
+        // The registry does not reference the mutex itself.
+        assert_eq!(m.weak_count(), 0);
+        assert_eq!(m.get_mut(), Some(&mut 1));
+
+        let weak = m.downgrade();
+        let upgraded = weak.upgrade().unwrap();
+        assert_eq!(find("registry-test").unwrap().handles, 2);
+        drop(upgraded);
+
+        let poisoner = m.clone();
+        let _ = thread::spawn(move || poisoner.update(|_| panic!("poison"))).join();
+        assert!(find("registry-test").unwrap().poisoned);
+        m.clear_poison();
+        assert!(!find("registry-test").unwrap().poisoned);
+
+        drop(m);
+        assert!(find("registry-test").is_none());
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn named_values_need_not_be_send() {
+        let m = EasyMutex::named("registry-rc-test", std::rc::Rc::new(1));
+        assert_eq!(find("registry-rc-test").unwrap().handles, 1);
+        m.with(|v| assert_eq!(**v, 1));
+    }
+}
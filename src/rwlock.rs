@@ -0,0 +1,214 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use crate::EasyMutexError;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A thread-safe, clonable wrapper around `std::sync::RwLock<T>` using `Arc`.
+///
+/// `EasyRwLock` is the reader-writer counterpart of [`EasyMutex`](crate::EasyMutex): it offers
+/// the same methods, but any number of threads can run [`EasyRwLock::read`] or
+/// [`EasyRwLock::with`] at the same time, while writes still get exclusive access.
+/// This makes it a better fit for read-heavy data such as configuration or routing tables.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::EasyRwLock;
+///
+/// let config = EasyRwLock::new(vec!["a".to_string()]);
+/// let clone = config.clone();
+///
+/// assert_eq!(config.with(|routes| routes.len()), 1);
+/// clone.update(|routes| routes.push("b".to_string()));
+/// assert_eq!(config.read(), vec!["a", "b"]);
+/// ```
+#[derive(Default, Debug)]
+pub struct EasyRwLock<T>(Arc<RwLock<T>>);
+
+impl<T> EasyRwLock<T> {
+    /// Creates a new `EasyRwLock` wrapping the given value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to wrap in a reader-writer lock.
+    ///
+    /// # Returns
+    ///
+    /// An `EasyRwLock` instance holding the provided value.
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(RwLock::new(value)))
+    }
+
+    /// Reads the inner value by acquiring a shared lock, cloning it and releasing it.
+    ///
+    /// # Returns
+    ///
+    /// A clone of the inner value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned (e.g., another thread panicked while holding the write lock).
+    pub fn read(&self) -> T
+    where
+        T: Clone,
+    {
+        self.read_lock().unwrap().clone()
+    }
+
+    /// Writes a new value by acquiring an exclusive lock, replacing the inner value and releasing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_value` - The new value to be stored in the lock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned (e.g., another thread panicked while holding the write lock).
+    pub fn write(&self, new_value: T) {
+        *self.write_lock().unwrap() = new_value;
+    }
+
+    /// Same as [`EasyRwLock::read`], but return a `Result<T, EasyMutexError>` type.
+    pub fn read_result(&self) -> Result<T, EasyMutexError>
+    where
+        T: Clone,
+    {
+        self.read_lock().map(|guard| guard.clone())
+    }
+
+    /// Same as [`EasyRwLock::write`], but return a `Result<(), EasyMutexError>` type.
+    pub fn write_result(&self, new_value: T) -> Result<(), EasyMutexError> {
+        self.write_lock().map(|mut guard| *guard = new_value)
+    }
+
+    /// Runs `f` against a shared reference to the value under a shared lock and returns its
+    /// output, without cloning the whole value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned (e.g., another thread panicked while holding the write lock).
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.read_lock().unwrap())
+    }
+
+    /// Same as [`EasyRwLock::with`], but return a `Result<R, EasyMutexError>` type.
+    pub fn with_result<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, EasyMutexError> {
+        self.read_lock().map(|guard| f(&guard))
+    }
+
+    /// Runs `f` against a mutable reference to the value under an exclusive lock and returns
+    /// its output. Behaves exactly like [`EasyRwLock::update`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned (e.g., another thread panicked while holding the write lock).
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        self.update(f)
+    }
+
+    /// Same as [`EasyRwLock::with_mut`], but return a `Result<R, EasyMutexError>` type.
+    pub fn with_mut_result<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, EasyMutexError> {
+        self.update_result(f)
+    }
+
+    /// Updates the inner value in place under a single exclusive lock acquisition.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned (e.g., another thread panicked while holding the write lock).
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.write_lock().unwrap())
+    }
+
+    /// Same as [`EasyRwLock::update`], but return a `Result<R, EasyMutexError>` type.
+    pub fn update_result<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, EasyMutexError> {
+        self.write_lock().map(|mut guard| f(&mut guard))
+    }
+
+    /// Acquires a shared lock, blocking the current thread until it is available.
+    fn read_lock(&self) -> Result<RwLockReadGuard<'_, T>, EasyMutexError> {
+        Ok(self.0.read()?)
+    }
+
+    /// Acquires an exclusive lock, blocking the current thread until it is available.
+    fn write_lock(&self) -> Result<RwLockWriteGuard<'_, T>, EasyMutexError> {
+        Ok(self.0.write()?)
+    }
+}
+
+/// Clones the handle, not the value: every clone shares the same lock.
+impl<T> Clone for EasyRwLock<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+/// Enables `EasyRwLock::from(value)` syntax.
+impl<T> From<T> for EasyRwLock<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EasyRwLock;
+    use crate::EasyMutexError;
+    use std::thread;
+
+    #[test]
+    fn basic_read_write() {
+        let lock = EasyRwLock::new(1);
+        let clone = lock.clone();
+
+        clone.write(2);
+        assert_eq!(lock.read(), 2);
+        assert_eq!(lock.update(|v| *v * 10), 20);
+        assert_eq!(lock.with(|v| *v + 1), 3);
+        assert_eq!(lock.write_result(4), Ok(()));
+        assert_eq!(lock.read_result(), Ok(4));
+
+        let from: EasyRwLock<String> = "hello".to_string().into();
+        assert_eq!(from.with_result(|s| s.len()), Ok(5));
+    }
+
+    #[test]
+    fn readers_share_the_lock() {
+        let lock = EasyRwLock::new(5);
+        let sum = lock.with(|outer| {
+            thread::scope(|s| s.spawn(|| lock.with(|inner| outer + inner)).join().unwrap())
+        });
+        assert_eq!(sum, 10);
+    }
+
+    #[test]
+    fn poisoned_lock_reports_error() {
+        let lock = EasyRwLock::new(0);
+        let poisoner = lock.clone();
+        let _ = thread::spawn(move || poisoner.update(|_| panic!("poison"))).join();
+
+        assert_eq!(lock.read_result(), Err(EasyMutexError::Poisoned));
+        assert_eq!(
+            lock.update_result(|v| *v += 1),
+            Err(EasyMutexError::Poisoned)
+        );
+    }
+}
@@ -0,0 +1,163 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use crate::{EasyMutex, EasyMutexError, EasyRwLock};
+
+/// A shared cell holding a `T`, abstracting over [`EasyMutex`] and [`EasyRwLock`].
+///
+/// Code written against this trait does not care which primitive backs the value, and can be
+/// tested with a mock implementation. Implementors only provide [`SharedCell::with_result`]
+/// and [`SharedCell::update_result`]; every other method is derived from them.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyMutex, EasyRwLock, SharedCell};
+///
+/// fn record_hit(hits: &impl SharedCell<u64>) -> u64 {
+///     hits.update(|count| {
+///         *count += 1;
+///         *count
+///     })
+/// }
+///
+/// let mutex = EasyMutex::new(0);
+/// let rwlock = EasyRwLock::new(10);
+/// assert_eq!(record_hit(&mutex), 1);
+/// assert_eq!(record_hit(&rwlock), 11);
+/// ```
+pub trait SharedCell<T> {
+    /// Runs `f` against a shared reference to the value and returns its output.
+    fn with_result<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, EasyMutexError>;
+
+    /// Runs `f` against a mutable reference to the value, as a single atomic update,
+    /// and returns its output.
+    fn update_result<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, EasyMutexError>;
+
+    /// Same as [`SharedCell::with_result`], but panics on error.
+    fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.with_result(f).unwrap()
+    }
+
+    /// Same as [`SharedCell::update_result`], but panics on error.
+    fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        self.update_result(f).unwrap()
+    }
+
+    /// Returns a clone of the value.
+    fn read_result(&self) -> Result<T, EasyMutexError>
+    where
+        T: Clone,
+    {
+        self.with_result(T::clone)
+    }
+
+    /// Replaces the value with `new_value`.
+    fn write_result(&self, new_value: T) -> Result<(), EasyMutexError> {
+        self.update_result(|value| *value = new_value)
+    }
+
+    /// Same as [`SharedCell::read_result`], but panics on error.
+    fn read(&self) -> T
+    where
+        T: Clone,
+    {
+        self.read_result().unwrap()
+    }
+
+    /// Same as [`SharedCell::write_result`], but panics on error.
+    fn write(&self, new_value: T) {
+        self.write_result(new_value).unwrap()
+    }
+}
+
+impl<T> SharedCell<T> for EasyMutex<T> {
+    fn with_result<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, EasyMutexError> {
+        EasyMutex::with_result(self, f)
+    }
+
+    fn update_result<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, EasyMutexError> {
+        EasyMutex::update_result(self, f)
+    }
+}
+
+impl<T> SharedCell<T> for EasyRwLock<T> {
+    fn with_result<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, EasyMutexError> {
+        EasyRwLock::with_result(self, f)
+    }
+
+    fn update_result<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, EasyMutexError> {
+        EasyRwLock::update_result(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedCell;
+    use crate::{EasyMutex, EasyMutexError, EasyRwLock};
+    use std::cell::RefCell;
+
+    /// Single-threaded mock that fails every access when `broken` is set.
+    struct MockCell<T> {
+        value: RefCell<T>,
+        broken: bool,
+    }
+
+    impl<T> SharedCell<T> for MockCell<T> {
+        fn with_result<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, EasyMutexError> {
+            if self.broken {
+                return Err(EasyMutexError::Poisoned);
+            }
+            Ok(f(&self.value.borrow()))
+        }
+
+        fn update_result<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, EasyMutexError> {
+            if self.broken {
+                return Err(EasyMutexError::Poisoned);
+            }
+            Ok(f(&mut self.value.borrow_mut()))
+        }
+    }
+
+    fn append_and_count(cell: &impl SharedCell<Vec<u32>>) -> Result<usize, EasyMutexError> {
+        cell.update_result(|v| v.push(1))?;
+        let snapshot = cell.read_result()?;
+        Ok(snapshot.len())
+    }
+
+    #[test]
+    fn generic_code_runs_on_every_backend() {
+        assert_eq!(append_and_count(&EasyMutex::new(vec![0])), Ok(2));
+        assert_eq!(append_and_count(&EasyRwLock::new(vec![])), Ok(1));
+
+        let mock = MockCell {
+            value: RefCell::new(vec![0, 0]),
+            broken: false,
+        };
+        assert_eq!(append_and_count(&mock), Ok(3));
+        mock.write(vec![]);
+        assert_eq!(SharedCell::read(&mock), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn errors_are_propagated() {
+        let mock = MockCell {
+            value: RefCell::new(vec![]),
+            broken: true,
+        };
+        assert_eq!(append_and_count(&mock), Err(EasyMutexError::Poisoned));
+    }
+}
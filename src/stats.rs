@@ -0,0 +1,309 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+//! Per-mutex lock metrics, recorded only with the `stats` feature. Without it, the recorder
+//! and the hold timer are zero-sized and every call compiles to nothing.
+
+use std::time::Duration;
+
+#[cfg(feature = "stats")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "stats")]
+use std::time::Instant;
+
+/// Upper bounds of the buckets of [`LockStats::wait_buckets`] and [`LockStats::hold_buckets`].
+#[cfg(feature = "stats")]
+pub const LATENCY_BUCKETS: [Duration; 8] = [
+    Duration::from_micros(1),
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+    Duration::from_secs(10),
+];
+
+/// One bucket per bound of [`LATENCY_BUCKETS`], plus one for longer durations.
+#[cfg(feature = "stats")]
+const BUCKETS: usize = LATENCY_BUCKETS.len() + 1;
+
+/// Lock metrics of an [`EasyMutex`](crate::EasyMutex), returned by
+/// [`EasyMutex::stats`](crate::EasyMutex::stats).
+///
+/// Every clone of the handle shares the same counters. They are updated with independent
+/// atomic operations, so a snapshot taken under contention may be slightly inconsistent,
+/// e.g. count an acquisition whose wait time is not added yet.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::EasyMutex;
+///
+/// let counter = EasyMutex::new(0);
+/// counter.update(|c| *c += 1);
+/// assert_eq!(counter.read(), 1);
+///
+/// let stats = counter.stats();
+/// assert_eq!(stats.acquisitions, 2);
+/// assert_eq!(stats.contended, 0);
+///
+/// counter.reset_stats();
+/// assert_eq!(counter.stats().acquisitions, 0);
+/// ```
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LockStats {
+    /// Number of times the lock was acquired.
+    pub acquisitions: u64,
+    /// Acquisitions that found the lock held and had to wait for it.
+    pub contended: u64,
+    /// Total time spent waiting for the lock.
+    pub total_wait: Duration,
+    /// Longest single wait for the lock.
+    pub max_wait: Duration,
+    /// Total time the lock was held, excluding the time released by the `wait_` methods.
+    pub total_hold: Duration,
+    /// Longest time the lock was held at once, see [`LockStats::hold_buckets`].
+    pub max_hold: Duration,
+    /// Acquisitions that found the lock poisoned, whatever the
+    /// [`PoisonPolicy`](crate::PoisonPolicy) did about it.
+    pub poison_events: u64,
+    /// Histogram of the wait for every acquisition, zero when uncontended: entry `i` counts
+    /// the waits longer than `LATENCY_BUCKETS[i - 1]` and up to `LATENCY_BUCKETS[i]`, and the
+    /// last one the waits longer than every bound.
+    pub wait_buckets: [u64; BUCKETS],
+    /// Histogram of the hold times, with the same buckets as [`LockStats::wait_buckets`].
+    ///
+    /// Holds are counted per hold period, not per acquisition: the `wait_` methods of
+    /// [`EasyMutex`](crate::EasyMutex) release the lock while waiting for a write and take it
+    /// back on every wake-up, each time starting a new hold. Its total can therefore exceed
+    /// [`LockStats::acquisitions`].
+    pub hold_buckets: [u64; BUCKETS],
+}
+
+#[cfg(feature = "stats")]
+impl LockStats {
+    /// Adds the metrics of `other`, as if they were recorded by the same mutex.
+    pub(crate) fn add(&mut self, other: &LockStats) {
+        self.acquisitions += other.acquisitions;
+        self.contended += other.contended;
+        self.total_wait += other.total_wait;
+        self.max_wait = self.max_wait.max(other.max_wait);
+        self.total_hold += other.total_hold;
+        self.max_hold = self.max_hold.max(other.max_hold);
+        self.poison_events += other.poison_events;
+        for (sum, n) in self.wait_buckets.iter_mut().zip(other.wait_buckets) {
+            *sum += n;
+        }
+        for (sum, n) in self.hold_buckets.iter_mut().zip(other.hold_buckets) {
+            *sum += n;
+        }
+    }
+}
+
+/// The counters behind [`LockStats`].
+#[cfg(feature = "stats")]
+#[derive(Default)]
+pub(crate) struct Recorder {
+    acquisitions: AtomicU64,
+    contended: AtomicU64,
+    total_wait: AtomicU64,
+    max_wait: AtomicU64,
+    total_hold: AtomicU64,
+    max_hold: AtomicU64,
+    poison_events: AtomicU64,
+    wait_buckets: [AtomicU64; BUCKETS],
+    hold_buckets: [AtomicU64; BUCKETS],
+}
+
+/// Records how long the lock is held when dropped.
+#[cfg(feature = "stats")]
+pub(crate) struct Hold<'a> {
+    recorder: &'a Recorder,
+    since: Instant,
+}
+
+#[cfg(feature = "stats")]
+impl Recorder {
+    /// Records an acquisition, with the time spent waiting if the lock was contended.
+    pub(crate) fn acquired(&self, wait: Option<Duration>) {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        if let Some(wait) = wait {
+            self.contended.fetch_add(1, Ordering::Relaxed);
+            add(&self.total_wait, &self.max_wait, wait);
+        }
+        observe(&self.wait_buckets, wait.unwrap_or_default());
+    }
+
+    /// Starts timing how long the lock is held.
+    pub(crate) fn hold(&self) -> Hold<'_> {
+        Hold {
+            recorder: self,
+            since: Instant::now(),
+        }
+    }
+
+    pub(crate) fn poisoned(&self) {
+        self.poison_events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> LockStats {
+        let duration = |nanos: &AtomicU64| Duration::from_nanos(nanos.load(Ordering::Relaxed));
+        LockStats {
+            acquisitions: self.acquisitions.load(Ordering::Relaxed),
+            contended: self.contended.load(Ordering::Relaxed),
+            total_wait: duration(&self.total_wait),
+            max_wait: duration(&self.max_wait),
+            total_hold: duration(&self.total_hold),
+            max_hold: duration(&self.max_hold),
+            poison_events: self.poison_events.load(Ordering::Relaxed),
+            wait_buckets: self
+                .wait_buckets
+                .each_ref()
+                .map(|n| n.load(Ordering::Relaxed)),
+            hold_buckets: self
+                .hold_buckets
+                .each_ref()
+                .map(|n| n.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        for counter in [
+            &self.acquisitions,
+            &self.contended,
+            &self.total_wait,
+            &self.max_wait,
+            &self.total_hold,
+            &self.max_hold,
+            &self.poison_events,
+        ]
+        .into_iter()
+        .chain(&self.wait_buckets)
+        .chain(&self.hold_buckets)
+        {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(feature = "stats")]
+impl Drop for Hold<'_> {
+    fn drop(&mut self) {
+        let recorder = self.recorder;
+        let hold = self.since.elapsed();
+        add(&recorder.total_hold, &recorder.max_hold, hold);
+        observe(&recorder.hold_buckets, hold);
+    }
+}
+
+/// Adds `duration` to a total and a maximum, both in nanoseconds.
+#[cfg(feature = "stats")]
+fn add(total: &AtomicU64, max: &AtomicU64, duration: Duration) {
+    let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+    total.fetch_add(nanos, Ordering::Relaxed);
+    max.fetch_max(nanos, Ordering::Relaxed);
+}
+
+/// Counts `duration` in its histogram bucket.
+#[cfg(feature = "stats")]
+fn observe(buckets: &[AtomicU64; BUCKETS], duration: Duration) {
+    let bucket = LATENCY_BUCKETS
+        .iter()
+        .position(|&bound| duration <= bound)
+        .unwrap_or(LATENCY_BUCKETS.len());
+    buckets[bucket].fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg(not(feature = "stats"))]
+#[derive(Default)]
+pub(crate) struct Recorder {}
+
+#[cfg(not(feature = "stats"))]
+pub(crate) struct Hold<'a>(std::marker::PhantomData<&'a Recorder>);
+
+#[cfg(not(feature = "stats"))]
+impl Recorder {
+    pub(crate) fn acquired(&self, _wait: Option<Duration>) {}
+
+    pub(crate) fn hold(&self) -> Hold<'_> {
+        Hold(std::marker::PhantomData)
+    }
+
+    pub(crate) fn poisoned(&self) {}
+}
+
+#[cfg(all(test, feature = "stats"))]
+mod tests {
+    use crate::EasyMutex;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn contention_and_hold_times_are_recorded() {
+        let m = EasyMutex::new(0);
+        let holder = m.clone();
+        let (locked, wait) = std::sync::mpsc::channel();
+        let handle = thread::spawn(move || {
+            holder.update(|v| {
+                locked.send(()).unwrap();
+                thread::sleep(Duration::from_millis(20));
+                *v += 1;
+            })
+        });
+
+        wait.recv().unwrap();
+        assert_eq!(m.read(), 1);
+        handle.join().unwrap();
+
+        let stats = m.stats();
+        assert_eq!(stats.acquisitions, 2);
+        assert_eq!(stats.contended, 1);
+        assert!(stats.max_wait > Duration::ZERO);
+        assert!(stats.total_wait >= stats.max_wait);
+        assert!(stats.max_hold >= Duration::from_millis(20));
+        assert!(stats.total_hold >= stats.max_hold);
+        assert_eq!(stats.poison_events, 0);
+        assert_eq!(stats.wait_buckets.iter().sum::<u64>(), 2);
+        assert_eq!(stats.hold_buckets.iter().sum::<u64>(), 2);
+        // The 20ms hold lands in the (10ms, 100ms] bucket.
+        assert_eq!(stats.hold_buckets[5], 1);
+    }
+
+    #[test]
+    fn poison_events_and_reset() {
+        let m = EasyMutex::new(1);
+        let poisoner = m.clone();
+        let _ = thread::spawn(move || poisoner.update(|_| panic!("poison"))).join();
+
+        assert!(m.read_result().is_err());
+        assert!(m.try_read().is_err());
+        assert_eq!(m.stats().poison_events, 2);
+
+        // Reading or repairing the poisoned value still counts as an acquisition.
+        assert_eq!(m.read_poisoned(), 1);
+        m.recover_with(|v| *v = 2);
+        let stats = m.stats();
+        assert_eq!((stats.acquisitions, stats.poison_events), (5, 4));
+        assert_eq!(m.read(), 2);
+        assert_eq!(m.stats().poison_events, 4);
+
+        m.reset_stats();
+        assert_eq!(m.stats(), Default::default());
+    }
+}
@@ -0,0 +1,413 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+//! Software transactional memory on top of [`EasyMutex`].
+//!
+//! Every [`EasyTVar`] is an `EasyMutex` holding its value and the version of the transaction
+//! that last wrote it. Versions come from a global clock, as in the TL2 algorithm: a
+//! transaction samples the clock when it starts and only accepts values that are not newer.
+//! Writes are buffered and published at commit time, with every cell involved locked in
+//! address order; if any of them changed in the meantime, the transaction runs again.
+
+use crate::{EasyMutex, LockGuard};
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+/// Global version clock, incremented by every committed write.
+static CLOCK: AtomicU64 = AtomicU64::new(0);
+
+/// A value that can be read and written atomically together with other `EasyTVar`s through
+/// [`atomically`].
+///
+/// Like [`EasyMutex`], an `EasyTVar` is a cheap cloneable handle: every clone refers to the
+/// same cell.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyTVar, atomically};
+///
+/// let checking = EasyTVar::new(100);
+/// let savings = EasyTVar::new(0);
+///
+/// atomically(|tx| {
+///     let amount = tx.read(&checking)?.min(30);
+///     tx.modify(&checking, |v| v - amount)?;
+///     tx.modify(&savings, |v| v + amount)?;
+///     Ok(())
+/// });
+/// assert_eq!((checking.read(), savings.read()), (70, 30));
+/// ```
+pub struct EasyTVar<T>(EasyMutex<TVarCell<T>>);
+
+/// The value of an [`EasyTVar`] and the clock value of the write that produced it.
+struct TVarCell<T> {
+    version: u64,
+    value: T,
+}
+
+impl<T> EasyTVar<T> {
+    /// Creates a new `EasyTVar` holding the given value.
+    pub fn new(value: T) -> Self {
+        Self(EasyMutex::new(TVarCell { version: 0, value }))
+    }
+
+    /// Reads the current value outside of any transaction.
+    pub fn read(&self) -> T
+    where
+        T: Clone,
+    {
+        self.0.with(|cell| cell.value.clone())
+    }
+
+    /// Writes a new value outside of any transaction.
+    ///
+    /// Transactions that already read this cell will be retried.
+    pub fn write(&self, new_value: T) {
+        self.0.update(|cell| {
+            cell.version = CLOCK.fetch_add(1, Ordering::AcqRel) + 1;
+            cell.value = new_value;
+        })
+    }
+}
+
+impl<T> Clone for EasyTVar<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Default> Default for EasyTVar<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for EasyTVar<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_tuple("EasyTVar");
+        match self.0.0.mutex.try_lock() {
+            Ok(cell) => d.field(&cell.value),
+            Err(_) => d.field(&format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+/// Enables `EasyTVar::from(value)` syntax.
+impl<T> From<T> for EasyTVar<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Returned by [`Transaction`] methods when the transaction observed a conflicting write and
+/// must be retried. Propagate it with `?` and [`atomically`] runs the closure again.
+///
+/// It can only be created by a [`Transaction`]: the retry happens right away, so it cannot be
+/// used to wait for a condition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TxConflict(());
+
+impl fmt::Display for TxConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("transaction conflicted with a concurrent write")
+    }
+}
+
+impl Error for TxConflict {}
+
+/// The log of a running transaction, handed to the closure of [`atomically`].
+pub struct Transaction {
+    read_version: u64,
+    entries: BTreeMap<usize, Entry>,
+}
+
+/// What a transaction did with one cell.
+struct Entry {
+    tvar: Box<dyn AnyTVar>,
+    /// Version seen by the first read, if the transaction read the cell before writing it.
+    read: Option<u64>,
+    /// Value to publish on commit.
+    write: Option<Box<dyn Any + Send>>,
+}
+
+impl Transaction {
+    fn new() -> Self {
+        Self {
+            read_version: CLOCK.load(Ordering::Acquire),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Reads the value of `tvar` as seen by this transaction.
+    ///
+    /// Returns a value written earlier in the same transaction if there is one. Otherwise,
+    /// returns `Err(TxConflict)` if the cell was written after the transaction started.
+    pub fn read<T>(&mut self, tvar: &EasyTVar<T>) -> Result<T, TxConflict>
+    where
+        T: Clone + Send + 'static,
+    {
+        let addr = tvar.0.addr();
+        if let Some(value) = self
+            .entries
+            .get(&addr)
+            .and_then(|entry| entry.write.as_ref())
+        {
+            return Ok(value.downcast_ref::<T>().unwrap().clone());
+        }
+        let (version, value) = tvar.0.with(|cell| (cell.version, cell.value.clone()));
+        if version > self.read_version {
+            return Err(TxConflict(()));
+        }
+        self.entry(tvar).read.get_or_insert(version);
+        Ok(value)
+    }
+
+    /// Writes `new_value` into `tvar`. The write becomes visible to other threads only when
+    /// the transaction commits.
+    pub fn write<T>(&mut self, tvar: &EasyTVar<T>, new_value: T)
+    where
+        T: Send + 'static,
+    {
+        self.entry(tvar).write = Some(Box::new(new_value));
+    }
+
+    /// Replaces the value of `tvar` with the result of `f` applied to it.
+    pub fn modify<T>(
+        &mut self,
+        tvar: &EasyTVar<T>,
+        f: impl FnOnce(T) -> T,
+    ) -> Result<(), TxConflict>
+    where
+        T: Clone + Send + 'static,
+    {
+        let value = self.read(tvar)?;
+        self.write(tvar, f(value));
+        Ok(())
+    }
+
+    fn entry<T>(&mut self, tvar: &EasyTVar<T>) -> &mut Entry
+    where
+        T: Send + 'static,
+    {
+        self.entries.entry(tvar.0.addr()).or_insert_with(|| Entry {
+            tvar: Box::new(tvar.clone()),
+            read: None,
+            write: None,
+        })
+    }
+
+    /// Publishes the buffered writes, or returns `false` if a cell read by the transaction
+    /// was written since it started.
+    fn commit(self) -> bool {
+        if self.entries.values().all(|entry| entry.write.is_none()) {
+            // Every read was already checked against the start version.
+            return true;
+        }
+        let (tvars, logs): (Vec<_>, Vec<_>) = self
+            .entries
+            .into_values()
+            .map(|entry| (entry.tvar, (entry.read, entry.write)))
+            .unzip();
+        // The map is ordered by address, so every committer locks cells in the same order.
+        let mut guards: Vec<_> = tvars.iter().map(|tvar| tvar.lock()).collect();
+        if guards
+            .iter()
+            .zip(&logs)
+            .any(|(guard, (read, _))| read.is_some() && guard.version() > self.read_version)
+        {
+            return false;
+        }
+        // Taking the new version while holding every lock guarantees that a transaction
+        // starting after this point cannot read any of these cells before they are updated.
+        let write_version = CLOCK.fetch_add(1, Ordering::AcqRel) + 1;
+        for (guard, (_, write)) in guards.iter_mut().zip(logs) {
+            if let Some(value) = write {
+                guard.store(value, write_version);
+            }
+        }
+        true
+    }
+}
+
+/// Runs `f` as a transaction and returns its output.
+///
+/// All the reads of the transaction see a consistent snapshot, and all its writes become
+/// visible together. When a conflicting write is detected, the buffered writes are discarded
+/// and `f` runs again, so it should not have side effects other than through the
+/// [`Transaction`].
+///
+/// # Panics
+///
+/// Panics if an `EasyTVar` involved is poisoned.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::{EasyTVar, atomically};
+/// use std::thread;
+///
+/// let a = EasyTVar::new(50);
+/// let b = EasyTVar::new(50);
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let (a, b) = (a.clone(), b.clone());
+///         thread::spawn(move || {
+///             for _ in 0..100 {
+///                 atomically(|tx| {
+///                     tx.modify(&a, |v| v - 1)?;
+///                     tx.modify(&b, |v| v + 1)
+///                 });
+///             }
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// assert_eq!((a.read(), b.read()), (-350, 450));
+/// ```
+pub fn atomically<R>(mut f: impl FnMut(&mut Transaction) -> Result<R, TxConflict>) -> R {
+    loop {
+        let mut tx = Transaction::new();
+        if let Ok(result) = f(&mut tx) {
+            if tx.commit() {
+                return result;
+            }
+        }
+        thread::yield_now();
+    }
+}
+
+/// Type-erased access to an [`EasyTVar`], so a transaction can hold cells of any type.
+trait AnyTVar: Send {
+    fn lock(&self) -> Box<dyn LockedTVar + '_>;
+}
+
+/// A locked [`EasyTVar`] being committed.
+trait LockedTVar {
+    fn version(&self) -> u64;
+    fn store(&mut self, value: Box<dyn Any + Send>, version: u64);
+}
+
+impl<T: Send + 'static> AnyTVar for EasyTVar<T> {
+    fn lock(&self) -> Box<dyn LockedTVar + '_> {
+        Box::new(self.0.lock().unwrap())
+    }
+}
+
+impl<T: 'static> LockedTVar for LockGuard<'_, TVarCell<T>> {
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn store(&mut self, value: Box<dyn Any + Send>, version: u64) {
+        self.value = *value.downcast().unwrap();
+        self.version = version;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EasyTVar, TxConflict, atomically};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn transactions_see_their_own_writes() {
+        let a = EasyTVar::new(1);
+        let b = EasyTVar::new(String::from("x"));
+
+        let (seen, len) = atomically(|tx| {
+            tx.write(&a, 2);
+            tx.modify(&b, |s| s + "y")?;
+            Ok((tx.read(&a)?, tx.read(&b)?.len()))
+        });
+        assert_eq!((seen, len), (2, 2));
+        assert_eq!((a.read(), b.read()), (2, String::from("xy")));
+    }
+
+    #[test]
+    fn conflicting_write_retries_transaction() {
+        let a = EasyTVar::new(0);
+        let runs = AtomicUsize::new(0);
+
+        let result = atomically(|tx| {
+            let value = tx.read(&a)?;
+            if runs.fetch_add(1, Ordering::SeqCst) == 0 {
+                // Simulate another thread committing after our read.
+                a.write(10);
+            }
+            tx.write(&a, value + 1);
+            Ok(value)
+        });
+        assert_eq!(result, 10);
+        assert_eq!(a.read(), 11);
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn explicit_conflict_retries() {
+        let a = EasyTVar::new(0);
+        let mut attempts = 0;
+        atomically(|tx| {
+            attempts += 1;
+            tx.write(&a, attempts);
+            if attempts < 3 {
+                Err(TxConflict(()))
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(a.read(), 3);
+    }
+
+    #[test]
+    fn invariant_holds_under_contention() {
+        let accounts: Vec<_> = (0..4).map(|_| EasyTVar::new(100i64)).collect();
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let accounts = accounts.clone();
+                thread::spawn(move || {
+                    for i in 0..500 {
+                        let from = &accounts[(t + i) % 4];
+                        let to = &accounts[(t + i + 1) % 4];
+                        atomically(|tx| {
+                            tx.modify(from, |v| v - 1)?;
+                            tx.modify(to, |v| v + 1)
+                        });
+                        let total = atomically(|tx| {
+                            accounts.iter().try_fold(0, |sum, a| Ok(sum + tx.read(a)?))
+                        });
+                        assert_eq!(total, 400);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(accounts.iter().map(EasyTVar::read).sum::<i64>(), 400);
+    }
+}
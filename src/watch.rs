@@ -0,0 +1,554 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use crate::{EasyMutex, EasyMutexError, Inner};
+use std::fmt;
+use std::future;
+use std::mem;
+use std::sync::atomic::{self, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak};
+use std::task::{Context, Poll, Waker};
+
+/// A subscription to the writes of an [`EasyMutex`], created with [`EasyMutex::subscribe`].
+///
+/// A watcher only remembers the last version it has seen, not the values in between: after
+/// several writes, [`EasyWatcher::changed`] returns once and [`EasyWatcher::read`] gives the
+/// latest value. The watcher does not keep the mutex alive, and dropping it detaches it.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::EasyMutex;
+/// use std::thread;
+///
+/// let status = EasyMutex::new("starting");
+/// let mut watcher = status.subscribe();
+///
+/// let handle = thread::spawn(move || {
+///     while watcher.changed().is_ok() {
+///         if watcher.read() == Some("done") {
+///             return true;
+///         }
+///     }
+///     false
+/// });
+///
+/// status.write("busy");
+/// status.write("done");
+/// assert!(handle.join().unwrap());
+/// ```
+pub struct EasyWatcher<T> {
+    mutex: Weak<Inner<T>>,
+    channel: Arc<Channel>,
+    seen: u64,
+}
+
+/// The async counterpart of [`EasyWatcher`], created with [`EasyMutex::subscribe_async`].
+///
+/// [`EasyAsyncWatcher::changed`] resolves on the next write instead of blocking, and
+/// [`EasyAsyncWatcher::next`] (or [`EasyAsyncWatcher::poll_next`], shaped like
+/// `Stream::poll_next`) yields a snapshot of the latest value after every change, skipping
+/// intermediate values, and `None` once the mutex has been dropped.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::EasyMutex;
+/// use std::pin::pin;
+/// use std::task::{Context, Poll, Waker};
+///
+/// let config = EasyMutex::new(1);
+/// let mut watcher = config.subscribe_async();
+/// let mut cx = Context::from_waker(Waker::noop());
+///
+/// // Normally awaited from an executor, which the write would wake up.
+/// assert!(watcher.poll_next(&mut cx).is_pending());
+/// config.write(2);
+/// config.write(3);
+/// assert_eq!(pin!(watcher.next()).poll(&mut cx), Poll::Ready(Some(3)));
+///
+/// drop(config);
+/// assert_eq!(pin!(watcher.next()).poll(&mut cx), Poll::Ready(None));
+/// ```
+pub struct EasyAsyncWatcher<T> {
+    watcher: EasyWatcher<T>,
+    /// Identifies the waker of this watcher in the channel.
+    id: u64,
+}
+
+/// The sending side of the watch channel, owned by the shared state of the mutex.
+///
+/// Dropping it, which happens with the last `EasyMutex` handle, closes the channel.
+#[derive(Default)]
+pub(crate) struct WatchSender(Arc<Channel>);
+
+#[derive(Default)]
+struct Channel {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    version: u64,
+    closed: bool,
+    next_id: u64,
+    /// Wakers of the pending async watchers, taken on every change.
+    wakers: Vec<(u64, Waker)>,
+}
+
+impl State {
+    /// Marks the pending change as seen, if any, or reports that the channel is closed.
+    fn take_change(&self, seen: &mut u64) -> Option<Result<(), EasyMutexError>> {
+        if self.version > *seen {
+            *seen = self.version;
+            Some(Ok(()))
+        } else if self.closed {
+            Some(Err(EasyMutexError::Closed))
+        } else {
+            None
+        }
+    }
+
+    /// Wakes the blocked watchers and takes the wakers of the pending async watchers.
+    fn notify(mut state: MutexGuard<'_, State>, changed: &Condvar) -> Wakers {
+        let wakers = mem::take(&mut state.wakers);
+        drop(state);
+        changed.notify_all();
+        Wakers(wakers)
+    }
+}
+
+/// The wakers of async watchers to wake after a write.
+///
+/// They are woken when this is dropped, which the write path does only after releasing its
+/// locks, since a waker may poll the watcher right away.
+#[derive(Default)]
+pub(crate) struct Wakers(Vec<(u64, Waker)>);
+
+impl Drop for Wakers {
+    fn drop(&mut self) {
+        for (_, waker) in self.0.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+impl Channel {
+    fn state(&self) -> MutexGuard<'_, State> {
+        // No user code runs under this lock, so it cannot be poisoned in a meaningful way.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl WatchSender {
+    /// Returns the number of live watchers, each holding a weak reference to the mutex.
+    pub(crate) fn watchers(&self) -> usize {
+        Arc::strong_count(&self.0) - 1
+    }
+
+    /// Wakes every blocked watcher after the mutex reached `version`, if there is any, and
+    /// returns the async watchers to wake.
+    pub(crate) fn publish(&self, version: u64) -> Wakers {
+        // Pairs with the fence in `EasyWatcher::attach`: either the new watcher is counted here,
+        // or it reads `version` as already seen.
+        atomic::fence(Ordering::SeqCst);
+        if self.watchers() == 0 {
+            return Wakers::default();
+        }
+        let mut state = self.0.state();
+        state.version = version;
+        State::notify(state, &self.0.changed)
+    }
+}
+
+impl Drop for WatchSender {
+    fn drop(&mut self) {
+        let mut state = self.0.state();
+        state.closed = true;
+        drop(State::notify(state, &self.0.changed));
+    }
+}
+
+impl<T> EasyWatcher<T> {
+    /// Creates a watcher that has already seen the current value of `mutex`.
+    pub(crate) fn attach(mutex: &EasyMutex<T>) -> Self {
+        let channel = Arc::clone(&mutex.0.watch.0);
+        // The channel is not updated while nobody watches, so the version must be read after
+        // the watcher is counted by `WatchSender::publish`.
+        atomic::fence(Ordering::SeqCst);
+        Self {
+            mutex: Arc::downgrade(&mutex.0),
+            channel,
+            seen: mutex.version(),
+        }
+    }
+
+    /// Blocks the current thread until the mutex is written after the last value seen by this
+    /// watcher, then marks the change as seen.
+    ///
+    /// Returns right away if a write is already pending, however many writes happened since.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on a change, or `Err(EasyMutexError::Closed)` once every handle to the mutex
+    /// has been dropped and no change is left to see.
+    pub fn changed(&mut self) -> Result<(), EasyMutexError> {
+        let mut state = self.channel.state();
+        loop {
+            if let Some(result) = state.take_change(&mut self.seen) {
+                return result;
+            }
+            state = self
+                .channel
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Returns `true` if the mutex has been written since the last value seen by this watcher,
+    /// without blocking nor marking the change as seen.
+    ///
+    /// Returns `Err(EasyMutexError::Closed)` once every handle to the mutex has been dropped
+    /// and no change is left to see.
+    pub fn has_changed(&self) -> Result<bool, EasyMutexError> {
+        let state = self.channel.state();
+        if state.version > self.seen {
+            Ok(true)
+        } else if state.closed {
+            Err(EasyMutexError::Closed)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Reads the latest value and marks it as seen, or returns `None` if the mutex has been
+    /// dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned, unless its [`PoisonPolicy`](crate::PoisonPolicy)
+    /// recovers from it.
+    pub fn read(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    /// Borrows the latest value and marks it as seen, or returns `None` if the mutex has been
+    /// dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned, unless its [`PoisonPolicy`](crate::PoisonPolicy)
+    /// recovers from it.
+    pub fn with<R>(&mut self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let mutex = EasyMutex::from_inner(self.mutex.upgrade()?);
+        let (result, version) = mutex.with(|value| (f(value), mutex.version()));
+        self.seen = self.seen.max(version);
+        Some(result)
+    }
+}
+
+impl<T> Clone for EasyWatcher<T> {
+    fn clone(&self) -> Self {
+        Self {
+            mutex: Weak::clone(&self.mutex),
+            channel: Arc::clone(&self.channel),
+            seen: self.seen,
+        }
+    }
+}
+
+impl<T> fmt::Debug for EasyWatcher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EasyWatcher")
+            .field("seen", &self.seen)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> EasyAsyncWatcher<T> {
+    pub(crate) fn attach(mutex: &EasyMutex<T>) -> Self {
+        Self::from(EasyWatcher::attach(mutex))
+    }
+
+    /// Resolves once the mutex is written after the last value seen by this watcher, then
+    /// marks the change as seen.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on a change, or `Err(EasyMutexError::Closed)` once every handle to the mutex
+    /// has been dropped and no change is left to see.
+    pub async fn changed(&mut self) -> Result<(), EasyMutexError> {
+        future::poll_fn(|cx| self.poll_changed(cx)).await
+    }
+
+    /// Polls for a change, registering the waker of `cx` if there is none yet.
+    pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), EasyMutexError>> {
+        let mut state = self.watcher.channel.state();
+        if let Some(result) = state.take_change(&mut self.watcher.seen) {
+            return Poll::Ready(result);
+        }
+        match state.wakers.iter_mut().find(|(id, _)| *id == self.id) {
+            Some((_, waker)) => waker.clone_from(cx.waker()),
+            None => state.wakers.push((self.id, cx.waker().clone())),
+        }
+        Poll::Pending
+    }
+
+    /// Resolves on the next change with a clone of the latest value, or `None` once the mutex
+    /// has been dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned, unless its [`PoisonPolicy`](crate::PoisonPolicy)
+    /// recovers from it.
+    pub async fn next(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        future::poll_fn(|cx| self.poll_next(cx)).await
+    }
+
+    /// Same as [`EasyAsyncWatcher::next`], in the shape of `Stream::poll_next`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned, unless its [`PoisonPolicy`](crate::PoisonPolicy)
+    /// recovers from it.
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>>
+    where
+        T: Clone,
+    {
+        self.poll_changed(cx).map(|result| match result {
+            Ok(()) => self.watcher.read(),
+            Err(_) => None,
+        })
+    }
+
+    /// Same as [`EasyWatcher::read`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned, unless its [`PoisonPolicy`](crate::PoisonPolicy)
+    /// recovers from it.
+    pub fn read(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.watcher.read()
+    }
+
+    /// Same as [`EasyWatcher::with`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned, unless its [`PoisonPolicy`](crate::PoisonPolicy)
+    /// recovers from it.
+    pub fn with<R>(&mut self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.watcher.with(f)
+    }
+}
+
+impl<T> Clone for EasyAsyncWatcher<T> {
+    fn clone(&self) -> Self {
+        Self::from(self.watcher.clone())
+    }
+}
+
+impl<T> Drop for EasyAsyncWatcher<T> {
+    fn drop(&mut self) {
+        let mut state = self.watcher.channel.state();
+        state.wakers.retain(|(id, _)| *id != self.id);
+    }
+}
+
+impl<T> fmt::Debug for EasyAsyncWatcher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EasyAsyncWatcher")
+            .field("seen", &self.watcher.seen)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Turns a blocking watcher into an async one, keeping the last value it has seen.
+impl<T> From<EasyWatcher<T>> for EasyAsyncWatcher<T> {
+    fn from(watcher: EasyWatcher<T>) -> Self {
+        let id = {
+            let mut state = watcher.channel.state();
+            state.next_id += 1;
+            state.next_id
+        };
+        Self { watcher, id }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EasyMutex, EasyMutexError};
+    use std::pin::pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Counts how many times it was woken.
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Reads the mutex when woken, as an executor polling the watcher right away would.
+    struct ReadingWaker {
+        mutex: EasyMutex<i32>,
+        read: Mutex<Option<Result<i32, EasyMutexError>>>,
+    }
+
+    impl Wake for ReadingWaker {
+        fn wake(self: Arc<Self>) {
+            *self.read.lock().unwrap() = Some(self.mutex.try_read());
+        }
+    }
+
+    #[test]
+    fn watcher_skips_intermediate_values() {
+        let m = EasyMutex::new(0);
+        let mut watcher = m.subscribe();
+        assert_eq!(watcher.has_changed(), Ok(false));
+
+        for i in 1..=3 {
+            m.write(i);
+        }
+        assert_eq!(watcher.has_changed(), Ok(true));
+        assert_eq!(watcher.changed(), Ok(()));
+        assert_eq!(watcher.read(), Some(3));
+        assert_eq!(watcher.has_changed(), Ok(false));
+
+        m.update(|v| *v += 1);
+        assert_eq!(watcher.with(|v| *v * 10), Some(40));
+        assert_eq!(watcher.has_changed(), Ok(false));
+    }
+
+    #[test]
+    fn unwatched_writes_skip_the_channel() {
+        let m = EasyMutex::new(0);
+        drop(m.subscribe());
+        m.write(1);
+        m.write(2);
+        assert_eq!(m.0.watch.0.state().version, 0);
+
+        let mut watcher = m.subscribe();
+        assert_eq!(watcher.has_changed(), Ok(false));
+        m.write(3);
+        assert_eq!(watcher.changed(), Ok(()));
+        assert_eq!(watcher.read(), Some(3));
+    }
+
+    #[test]
+    fn watcher_is_woken_by_writes_from_another_thread() {
+        let m = EasyMutex::new(String::new());
+        let mut watcher = m.subscribe();
+        let handle = thread::spawn(move || {
+            watcher.changed().unwrap();
+            watcher.read().unwrap()
+        });
+
+        thread::sleep(Duration::from_millis(5));
+        m.write("ready".to_string());
+        assert_eq!(handle.join().unwrap(), "ready");
+    }
+
+    #[test]
+    fn dropping_the_mutex_closes_the_channel() {
+        let m = EasyMutex::new(1);
+        let mut watcher = m.subscribe();
+        drop(m.subscribe());
+
+        m.write(2);
+        drop(m);
+        // The last change is still reported before the channel is closed.
+        assert_eq!(watcher.changed(), Ok(()));
+        assert_eq!(watcher.read(), None);
+        assert_eq!(watcher.changed(), Err(EasyMutexError::Closed));
+        assert_eq!(watcher.has_changed(), Err(EasyMutexError::Closed));
+    }
+
+    #[test]
+    fn async_watcher_is_woken_by_writes() {
+        let m = EasyMutex::new(0);
+        let mut watcher = m.subscribe_async();
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        {
+            let mut changed = pin!(watcher.changed());
+            assert!(changed.as_mut().poll(&mut cx).is_pending());
+            assert!(changed.as_mut().poll(&mut cx).is_pending());
+            m.write(1);
+            m.write(2);
+            assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+            assert_eq!(changed.poll(&mut cx), Poll::Ready(Ok(())));
+        }
+        assert_eq!(watcher.read(), Some(2));
+    }
+
+    #[test]
+    fn async_watchers_are_woken_after_the_lock_is_released() {
+        let m = EasyMutex::new(0);
+        let mut watcher = m.subscribe_async();
+        let reader = Arc::new(ReadingWaker {
+            mutex: m.clone(),
+            read: Mutex::new(None),
+        });
+        let waker = Waker::from(reader.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(watcher.poll_next(&mut cx), Poll::Pending);
+        m.write(1);
+        assert_eq!(*reader.read.lock().unwrap(), Some(Ok(1)));
+    }
+
+    #[test]
+    fn async_watcher_streams_latest_values_until_closed() {
+        let m = EasyMutex::new("a");
+        let mut watcher = m.subscribe_async();
+        let mut other = watcher.clone();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        assert_eq!(watcher.poll_next(&mut cx), Poll::Pending);
+        m.write("b");
+        m.write("c");
+        assert_eq!(watcher.poll_next(&mut cx), Poll::Ready(Some("c")));
+        assert_eq!(watcher.poll_next(&mut cx), Poll::Pending);
+        assert_eq!(pin!(other.next()).poll(&mut cx), Poll::Ready(Some("c")));
+
+        drop(m);
+        assert_eq!(watcher.poll_next(&mut cx), Poll::Ready(None));
+        assert_eq!(
+            pin!(other.changed()).poll(&mut cx),
+            Poll::Ready(Err(EasyMutexError::Closed))
+        );
+    }
+}
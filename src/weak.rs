@@ -0,0 +1,149 @@
+/*
+  Copyright 2025 Marco Fabbroni
+
+  Licensed under the Apache License, Version 2.0 (the "License");
+  you may not use this file except in compliance with the License.
+  You may obtain a copy of the License at
+
+      http://www.apache.org/licenses/LICENSE-2.0
+
+  Unless required by applicable law or agreed to in writing, software
+  distributed under the License is distributed on an "AS IS" BASIS,
+  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+  See the License for the specific language governing permissions and
+  limitations under the License.
+*/
+
+use crate::{EasyMutex, Inner};
+use std::fmt;
+use std::sync::Weak;
+
+/// A non-owning handle to an [`EasyMutex`], created with [`EasyMutex::downgrade`].
+///
+/// An `EasyWeak` does not keep the value alive: once every `EasyMutex` handle is dropped,
+/// [`EasyWeak::upgrade`] returns `None` and so do the convenience accessors. This is the way
+/// to break reference cycles in caches and observer lists.
+///
+/// # Example
+///
+/// ```
+/// use easy_mutex::EasyMutex;
+///
+/// let shared = EasyMutex::new(1);
+/// let weak = shared.downgrade();
+///
+/// assert_eq!(weak.update(|v| *v += 1), Some(()));
+/// assert_eq!(weak.read(), Some(2));
+///
+/// drop(shared);
+/// assert!(weak.upgrade().is_none());
+/// assert_eq!(weak.read(), None);
+/// ```
+pub struct EasyWeak<T>(pub(crate) Weak<Inner<T>>);
+
+impl<T> EasyWeak<T> {
+    /// Creates a weak handle that is not attached to any mutex: [`EasyWeak::upgrade`] always
+    /// returns `None`.
+    pub fn new() -> Self {
+        Self(Weak::new())
+    }
+
+    /// Returns a strong [`EasyMutex`] handle, or `None` if the mutex has been dropped.
+    pub fn upgrade(&self) -> Option<EasyMutex<T>> {
+        self.0.upgrade().map(EasyMutex::from_inner)
+    }
+
+    /// Same as [`EasyMutex::read`], or `None` if the mutex has been dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned, unless its [`PoisonPolicy`](crate::PoisonPolicy)
+    /// recovers from it.
+    pub fn read(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.upgrade().map(|mutex| mutex.read())
+    }
+
+    /// Same as [`EasyMutex::with`], or `None` if the mutex has been dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned, unless its [`PoisonPolicy`](crate::PoisonPolicy)
+    /// recovers from it.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.upgrade().map(|mutex| mutex.with(f))
+    }
+
+    /// Same as [`EasyMutex::update`], or `None` if the mutex has been dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned, unless its [`PoisonPolicy`](crate::PoisonPolicy)
+    /// recovers from it.
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.upgrade().map(|mutex| mutex.update(f))
+    }
+}
+
+impl<T> Clone for EasyWeak<T> {
+    fn clone(&self) -> Self {
+        Self(Weak::clone(&self.0))
+    }
+}
+
+impl<T> Default for EasyWeak<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for EasyWeak<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(EasyWeak)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EasyWeak;
+    use crate::EasyMutex;
+
+    #[test]
+    fn weak_handle_does_not_keep_value_alive() {
+        let m = EasyMutex::new(vec![1]);
+        let weak = m.downgrade();
+        assert_eq!(m.weak_count(), 1);
+        assert_eq!(m.strong_count(), 1);
+
+        let upgraded = weak.upgrade().unwrap();
+        assert!(upgraded.ptr_eq(&m));
+        drop(upgraded);
+
+        assert_eq!(weak.update(|v| v.push(2)), Some(()));
+        assert_eq!(weak.with(|v| v.len()), Some(2));
+
+        drop(m);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(weak.read(), None);
+        assert_eq!(weak.update(|v| v.push(3)), None);
+    }
+
+    #[test]
+    fn cycle_is_broken_by_weak_handle() {
+        struct Node {
+            parent: EasyWeak<Node>,
+        }
+
+        let root = EasyMutex::new(Node {
+            parent: EasyWeak::new(),
+        });
+        root.update(|node| node.parent = root.downgrade());
+        let weak = root.downgrade();
+        assert!(root.with(|node| node.parent.upgrade().is_some()));
+
+        drop(root);
+        assert!(weak.upgrade().is_none());
+    }
+}